@@ -0,0 +1,5 @@
+CREATE TABLE messages (
+    thread_message_id TEXT NOT NULL PRIMARY KEY,
+    dm_message_id TEXT NOT NULL UNIQUE,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE
+);
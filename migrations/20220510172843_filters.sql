@@ -0,0 +1,4 @@
+CREATE TABLE filters (
+    filter_id INTEGER NOT NULL PRIMARY KEY,
+    pattern TEXT NOT NULL UNIQUE
+);
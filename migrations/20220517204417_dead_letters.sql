@@ -0,0 +1,9 @@
+CREATE TABLE dead_letters (
+    dead_letter_id INTEGER NOT NULL PRIMARY KEY,
+    room_id INTEGER REFERENCES rooms (room_id) ON DELETE SET NULL,
+    direction TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    error TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
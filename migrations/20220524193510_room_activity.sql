@@ -0,0 +1,4 @@
+ALTER TABLE rooms ADD COLUMN last_activity_at INTEGER NOT NULL DEFAULT 0;
+
+-- existing threads count as active from now, so they aren't all closed as inactive at once
+UPDATE rooms SET last_activity_at = strftime('%s', 'now');
@@ -0,0 +1,3 @@
+CREATE TABLE blocks (
+    user_id TEXT NOT NULL PRIMARY KEY
+);
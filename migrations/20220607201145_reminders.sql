@@ -0,0 +1,7 @@
+CREATE TABLE reminders (
+    reminder_id INTEGER NOT NULL PRIMARY KEY,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE,
+    channel_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    due_at INTEGER NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE rooms ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
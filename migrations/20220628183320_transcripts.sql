@@ -0,0 +1,7 @@
+CREATE TABLE transcripts (
+    transcript_id INTEGER NOT NULL PRIMARY KEY,
+    codename TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    closed_at INTEGER NOT NULL,
+    content TEXT NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE rooms ADD COLUMN claimed_by TEXT;
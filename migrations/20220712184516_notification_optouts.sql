@@ -0,0 +1,3 @@
+CREATE TABLE notification_optouts (
+    user_id TEXT NOT NULL PRIMARY KEY
+);
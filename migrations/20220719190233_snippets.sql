@@ -0,0 +1,4 @@
+CREATE TABLE snippets (
+    name TEXT NOT NULL PRIMARY KEY,
+    content TEXT NOT NULL
+);
@@ -0,0 +1,5 @@
+CREATE TABLE block_stripped_roles (
+    user_id TEXT NOT NULL REFERENCES blocks (user_id) ON DELETE CASCADE,
+    role_id TEXT NOT NULL,
+    PRIMARY KEY (user_id, role_id)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE rooms ADD COLUMN inbox_id TEXT;
+
+-- existing threads were all opened in the inbox configured at the time
+UPDATE rooms SET inbox_id = (SELECT value FROM config WHERE key = 'inbox');
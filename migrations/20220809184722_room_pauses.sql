@@ -0,0 +1,6 @@
+ALTER TABLE rooms ADD COLUMN pause TEXT;
+
+CREATE TABLE queued_messages (
+    thread_message_id TEXT NOT NULL PRIMARY KEY,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+CREATE TABLE aliases (
+    user_id TEXT NOT NULL PRIMARY KEY,
+    codename TEXT NOT NULL UNIQUE
+);
@@ -0,0 +1 @@
+ALTER TABLE rooms ADD COLUMN snoozed_until INTEGER;
@@ -0,0 +1,8 @@
+CREATE TABLE scheduled_messages (
+    scheduled_id INTEGER NOT NULL PRIMARY KEY,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE,
+    channel_id TEXT NOT NULL,
+    author_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    due_at INTEGER NOT NULL
+);
@@ -0,0 +1,10 @@
+ALTER TABLE rooms ADD COLUMN opened_at INTEGER;
+ALTER TABLE rooms ADD COLUMN first_staff_reply_at INTEGER;
+
+-- closed threads, without anything identifying their user
+CREATE TABLE thread_stats (
+    stat_id INTEGER NOT NULL PRIMARY KEY,
+    opened_at INTEGER,
+    first_staff_reply_at INTEGER,
+    closed_at INTEGER NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE rooms ADD COLUMN sla_alerted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+ALTER TABLE snippets ADD COLUMN emoji TEXT;
+CREATE UNIQUE INDEX snippets_emoji ON snippets (emoji);
@@ -0,0 +1,83 @@
+-- users may have a room per category, so user IDs can't stay unique. SQLite can't drop the
+-- constraint in place, and rebuilding `rooms` while other tables reference it would cascade
+-- deletes into them, so those are set aside and rebuilt along with it.
+CREATE TABLE rooms_backup AS SELECT * FROM rooms;
+CREATE TABLE messages_backup AS SELECT * FROM messages;
+CREATE TABLE dead_letters_backup AS SELECT * FROM dead_letters;
+CREATE TABLE reminders_backup AS SELECT * FROM reminders;
+CREATE TABLE queued_messages_backup AS SELECT * FROM queued_messages;
+CREATE TABLE scheduled_messages_backup AS SELECT * FROM scheduled_messages;
+
+DROP TABLE messages;
+DROP TABLE dead_letters;
+DROP TABLE reminders;
+DROP TABLE queued_messages;
+DROP TABLE scheduled_messages;
+DROP TABLE rooms;
+
+CREATE TABLE rooms (
+    room_id INTEGER NOT NULL PRIMARY KEY,
+    codename TEXT NOT NULL UNIQUE,
+    channel_id TEXT NOT NULL UNIQUE,
+    user_id TEXT NOT NULL,
+    last_activity_at INTEGER NOT NULL DEFAULT 0,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    priority INTEGER NOT NULL DEFAULT 0,
+    claimed_by TEXT,
+    inbox_id TEXT,
+    pause TEXT,
+    snoozed_until INTEGER,
+    opened_at INTEGER,
+    first_staff_reply_at INTEGER,
+    sla_alerted BOOLEAN NOT NULL DEFAULT FALSE,
+    category TEXT
+);
+CREATE UNIQUE INDEX rooms_user_category ON rooms (user_id, IFNULL(category, ''));
+
+CREATE TABLE messages (
+    thread_message_id TEXT NOT NULL PRIMARY KEY,
+    dm_message_id TEXT NOT NULL UNIQUE,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE
+);
+CREATE TABLE dead_letters (
+    dead_letter_id INTEGER NOT NULL PRIMARY KEY,
+    room_id INTEGER REFERENCES rooms (room_id) ON DELETE SET NULL,
+    direction TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    error TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE reminders (
+    reminder_id INTEGER NOT NULL PRIMARY KEY,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE,
+    channel_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    due_at INTEGER NOT NULL
+);
+CREATE TABLE queued_messages (
+    thread_message_id TEXT NOT NULL PRIMARY KEY,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE
+);
+CREATE TABLE scheduled_messages (
+    scheduled_id INTEGER NOT NULL PRIMARY KEY,
+    room_id INTEGER NOT NULL REFERENCES rooms (room_id) ON DELETE CASCADE,
+    channel_id TEXT NOT NULL,
+    author_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    due_at INTEGER NOT NULL
+);
+
+INSERT INTO rooms SELECT *, NULL FROM rooms_backup;
+INSERT INTO messages SELECT * FROM messages_backup;
+INSERT INTO dead_letters SELECT * FROM dead_letters_backup;
+INSERT INTO reminders SELECT * FROM reminders_backup;
+INSERT INTO queued_messages SELECT * FROM queued_messages_backup;
+INSERT INTO scheduled_messages SELECT * FROM scheduled_messages_backup;
+
+DROP TABLE rooms_backup;
+DROP TABLE messages_backup;
+DROP TABLE dead_letters_backup;
+DROP TABLE reminders_backup;
+DROP TABLE queued_messages_backup;
+DROP TABLE scheduled_messages_backup;
@@ -0,0 +1,2 @@
+-- scheduled messages are resolved through their room, whose channel can change with `/move`
+ALTER TABLE scheduled_messages DROP COLUMN channel_id;
@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+
+use crate::error::Result;
+use serenity::model::channel::{Attachment, AttachmentType};
+
+const SPOILER_PREFIX: &str = "SPOILER_";
+
+/// An attachment downloaded so it can be re-uploaded on the other side of a room.
+pub struct Download {
+    pub data: Vec<u8>,
+    pub filename: String,
+}
+
+impl Download {
+    pub fn as_attachment(&self) -> AttachmentType<'_> {
+        AttachmentType::Bytes {
+            data: Cow::Borrowed(&self.data),
+            filename: self.filename.clone(),
+        }
+    }
+}
+
+/// Which attachments may be forwarded, by their lowercase file extension without the dot.
+/// Forwards everything by default.
+#[derive(Debug, Default)]
+pub struct Policy {
+    /// If set, only these extensions are forwarded.
+    pub allowed: Option<Vec<String>>,
+    pub blocked: Vec<String>,
+}
+
+impl Policy {
+    pub fn allows(&self, filename: &str) -> bool {
+        let extension = extension(filename);
+        let allowed = match &self.allowed {
+            Some(allowed) => extension.as_ref().is_some_and(|ext| allowed.contains(ext)),
+            None => true,
+        };
+        allowed && extension.is_none_or(|ext| !self.blocked.contains(&ext))
+    }
+}
+
+fn extension(filename: &str) -> Option<String> {
+    match filename.rsplit_once('.') {
+        Some((name, ext)) if !name.is_empty() => Some(ext.to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Downloads the attachments `policy` allows, returning them along with notes on the ones
+/// it didn't, e.g. `[attachment blocked: .exe]`.
+pub async fn download(
+    attachments: &[Attachment],
+    policy: &Policy,
+) -> Result<(Vec<Download>, Vec<String>)> {
+    let mut downloads = Vec::with_capacity(attachments.len());
+    let mut blocked = Vec::new();
+    for attachment in attachments {
+        if !policy.allows(&attachment.filename) {
+            blocked.push(match extension(&attachment.filename) {
+                Some(ext) => format!("[attachment blocked: .{}]", ext),
+                None => format!("[attachment blocked: {}]", attachment.filename),
+            });
+            continue;
+        }
+
+        downloads.push(Download {
+            data: attachment.download().await.map_err(anyhow::Error::from)?,
+            filename: upload_name(&attachment.filename),
+        });
+    }
+
+    Ok((downloads, blocked))
+}
+
+/// Appends notes on blocked attachments to a message's content.
+pub fn with_notes(content: &str, notes: &[String]) -> String {
+    std::iter::once(content)
+        .chain(notes.iter().map(String::as_str))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits the notes added by [`with_notes`] back off a message's content, e.g. to put them
+/// back after editing it.
+pub fn split_notes(content: &str) -> (String, Vec<String>) {
+    let is_note = |line: &str| line.starts_with("[attachment blocked: ") && line.ends_with(']');
+    let lines: Vec<_> = content.split('\n').collect();
+    let body = lines
+        .iter()
+        .rposition(|line| !is_note(line))
+        .map_or(0, |last| last + 1);
+    (
+        lines[..body].join("\n"),
+        lines[body..].iter().map(|&note| note.to_owned()).collect(),
+    )
+}
+
+/// Discord marks spoilers only through the filename, so the prefix must survive re-uploading.
+fn upload_name(filename: &str) -> String {
+    match filename.get(..SPOILER_PREFIX.len()) {
+        // normalize the prefix, since Discord matches it case-sensitively
+        Some(prefix) if prefix.eq_ignore_ascii_case(SPOILER_PREFIX) => {
+            format!("{}{}", SPOILER_PREFIX, &filename[SPOILER_PREFIX.len()..])
+        }
+        _ => filename.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_notes, upload_name, with_notes, Policy};
+
+    #[test]
+    fn upload_name_keeps_spoilers() {
+        assert_eq!(upload_name("cat.png"), "cat.png");
+        assert_eq!(upload_name("SPOILER_cat.png"), "SPOILER_cat.png");
+        assert_eq!(upload_name("spoiler_cat.png"), "SPOILER_cat.png");
+        assert_eq!(upload_name("SPOIL.png"), "SPOIL.png");
+        assert_eq!(upload_name("ünï.png"), "ünï.png");
+    }
+
+    #[test]
+    fn policy_allows() {
+        let default = Policy::default();
+        assert!(default.allows("setup.exe"));
+        assert!(default.allows("README"));
+
+        let blocking = Policy {
+            allowed: None,
+            blocked: vec!["exe".into()],
+        };
+        assert!(!blocking.allows("setup.EXE"));
+        assert!(blocking.allows("cat.png"));
+        assert!(blocking.allows(".exe"));
+
+        let allowing = Policy {
+            allowed: Some(vec!["png".into(), "exe".into()]),
+            blocked: vec!["exe".into()],
+        };
+        assert!(allowing.allows("cat.png"));
+        assert!(!allowing.allows("setup.exe"));
+        assert!(!allowing.allows("notes.txt"));
+        assert!(!allowing.allows("README"));
+    }
+
+    #[test]
+    fn notes_are_appended() {
+        let notes = vec!["[attachment blocked: .exe]".to_owned()];
+        assert_eq!(with_notes("hi", &notes), "hi\n[attachment blocked: .exe]");
+        assert_eq!(with_notes("", &notes), "[attachment blocked: .exe]");
+        assert_eq!(with_notes("hi", &[]), "hi");
+    }
+
+    #[test]
+    fn notes_are_split_off() {
+        let notes = vec!["[attachment blocked: .exe]".to_owned()];
+        assert_eq!(
+            split_notes(&with_notes("hi\nthere", &notes)),
+            ("hi\nthere".to_owned(), notes.clone())
+        );
+        assert_eq!(split_notes(&with_notes("", &notes)), (String::new(), notes));
+        assert_eq!(split_notes("hi"), ("hi".to_owned(), Vec::new()));
+    }
+}
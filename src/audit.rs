@@ -0,0 +1,92 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Something that can be recorded in the audit channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Open,
+    Close,
+    Block,
+    Reply,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Open => write!(f, "open"),
+            Event::Close => write!(f, "close"),
+            Event::Block => write!(f, "block"),
+            Event::Reply => write!(f, "reply"),
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Event::Open),
+            "close" => Ok(Event::Close),
+            "block" => Ok(Event::Block),
+            "reply" => Ok(Event::Reply),
+            _ => Err(format!("unknown audit event `{}`", s)),
+        }
+    }
+}
+
+/// The events recorded in the audit channel, stored comma-separated.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Events(pub Vec<Event>);
+
+impl Events {
+    pub fn contains(&self, event: Event) -> bool {
+        self.0.contains(&event)
+    }
+}
+
+/// Only the events worth a look later on, leaving out the everyday ones.
+impl Default for Events {
+    fn default() -> Self {
+        Self(vec![Event::Close, Event::Block])
+    }
+}
+
+impl Display for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let events: Vec<_> = self.0.iter().map(|event| event.to_string()).collect();
+        write!(f, "{}", events.join(","))
+    }
+}
+
+impl FromStr for Events {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|event| !event.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Events};
+
+    #[test]
+    fn events_roundtrip() {
+        let events: Events = "open, reply".parse().unwrap();
+        assert_eq!(events, Events(vec![Event::Open, Event::Reply]));
+        assert_eq!(events.to_string(), "open,reply");
+        assert_eq!("".parse::<Events>().unwrap(), Events(Vec::new()));
+        assert!("open,edit".parse::<Events>().is_err());
+
+        assert!(Events::default().contains(Event::Block));
+        assert!(!Events::default().contains(Event::Reply));
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serenity::model::id::MessageId;
+
+/// Discord's limit on the length of a message's content.
+pub const MESSAGE_LIMIT: usize = 2000;
+
+/// Messages for a room waiting to be forwarded to its thread as one.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Batch {
+    pub content: String,
+    /// The first message in the batch, which the forwarded message is linked to.
+    pub first: MessageId,
+    generation: u64,
+}
+
+/// Result of adding a message to a room's batch.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Push {
+    /// A new batch was started, which the caller is responsible for flushing.
+    Started,
+    /// The message was added to a batch another caller will flush.
+    Joined,
+    /// The message would have made the batch too long, so it started a new batch after the
+    /// returned one, which the caller should forward right away.
+    Overflowed(Batch),
+}
+
+/// Result of checking whether a batch is ready to be flushed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Poll {
+    /// No messages were added since the last poll.
+    Ready(Batch),
+    /// Messages were added since the last poll, so the user may still be typing.
+    Pending,
+    /// The batch was already flushed by someone else.
+    Gone,
+}
+
+/// Coalesces rapid messages from users, so they can be forwarded together. Batches are kept
+/// per room, so a user's messages to rooms in different categories don't mix.
+#[derive(Default)]
+pub struct Batcher(Mutex<HashMap<i64, Batch>>);
+
+impl Batcher {
+    pub fn push(&self, room_id: i64, message: MessageId, content: &str) -> Push {
+        let mut batches = self.0.lock().unwrap();
+        match batches.get_mut(&room_id) {
+            Some(batch) if batch.content.len() + 1 + content.len() > MESSAGE_LIMIT => {
+                let generation = batch.generation + 1;
+                let full = std::mem::replace(
+                    batch,
+                    Batch {
+                        content: content.to_owned(),
+                        first: message,
+                        generation,
+                    },
+                );
+                Push::Overflowed(full)
+            }
+            Some(batch) => {
+                batch.content.push('\n');
+                batch.content.push_str(content);
+                batch.generation += 1;
+                Push::Joined
+            }
+            None => {
+                batches.insert(
+                    room_id,
+                    Batch {
+                        content: content.to_owned(),
+                        first: message,
+                        generation: 0,
+                    },
+                );
+                Push::Started
+            }
+        }
+    }
+
+    /// Takes the room's batch if nothing was added to it since the last poll, whose generation
+    /// is tracked in `seen`.
+    pub fn poll(&self, room_id: i64, seen: &mut u64) -> Poll {
+        let mut batches = self.0.lock().unwrap();
+        match batches.get(&room_id) {
+            Some(batch) if batch.generation == *seen => {
+                Poll::Ready(batches.remove(&room_id).unwrap())
+            }
+            Some(batch) => {
+                *seen = batch.generation;
+                Poll::Pending
+            }
+            None => Poll::Gone,
+        }
+    }
+
+    /// Takes the room's batch right away, e.g. to keep it ahead of a message that can't be
+    /// batched.
+    pub fn flush(&self, room_id: i64) -> Option<Batch> {
+        self.0.lock().unwrap().remove(&room_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Batcher, Poll, Push, MESSAGE_LIMIT};
+
+    #[test]
+    fn batch_messages() {
+        let batcher = Batcher::default();
+        let mut seen = 0;
+        assert_eq!(batcher.push(1, 10.into(), "hi"), Push::Started);
+        assert_eq!(batcher.push(1, 11.into(), "I need help"), Push::Joined);
+        assert_eq!(batcher.poll(1, &mut seen), Poll::Pending);
+
+        match batcher.poll(1, &mut seen) {
+            Poll::Ready(batch) => {
+                assert_eq!(batch.content, "hi\nI need help");
+                assert_eq!(batch.first, 10);
+            }
+            poll => panic!("expected ready batch, got {:?}", poll),
+        }
+        assert_eq!(batcher.poll(1, &mut seen), Poll::Gone);
+    }
+
+    #[test]
+    fn batch_overflow() {
+        let batcher = Batcher::default();
+        let mut seen = 0;
+        let long = "a".repeat(MESSAGE_LIMIT - 10);
+        batcher.push(1, 10.into(), &long);
+        match batcher.push(1, 11.into(), "this no longer fits") {
+            Push::Overflowed(batch) => assert_eq!(batch.content, long),
+            push => panic!("expected overflow, got {:?}", push),
+        }
+
+        // the original caller keeps waiting on the new batch
+        assert_eq!(batcher.poll(1, &mut seen), Poll::Pending);
+        match batcher.poll(1, &mut seen) {
+            Poll::Ready(batch) => assert_eq!(batch.first, 11),
+            poll => panic!("expected ready batch, got {:?}", poll),
+        }
+    }
+
+    #[test]
+    fn batch_flush() {
+        let batcher = Batcher::default();
+        let mut seen = 0;
+        batcher.push(1, 10.into(), "hi");
+        assert!(batcher.flush(1).is_some());
+        assert!(batcher.flush(1).is_none());
+        assert_eq!(batcher.poll(1, &mut seen), Poll::Gone);
+    }
+
+    #[test]
+    fn batch_rooms() {
+        let batcher = Batcher::default();
+        let (mut seen_first, mut seen_second) = (0, 0);
+        assert_eq!(batcher.push(1, 10.into(), "billing"), Push::Started);
+        assert_eq!(batcher.push(2, 11.into(), "appeal"), Push::Started);
+        assert_eq!(batcher.push(1, 12.into(), "invoice"), Push::Joined);
+
+        assert_eq!(batcher.poll(1, &mut seen_first), Poll::Pending);
+        match batcher.poll(2, &mut seen_second) {
+            Poll::Ready(batch) => {
+                assert_eq!(batch.content, "appeal");
+                assert_eq!(batch.first, 11);
+            }
+            poll => panic!("expected ready batch, got {:?}", poll),
+        }
+        match batcher.poll(1, &mut seen_first) {
+            Poll::Ready(batch) => assert_eq!(batch.content, "billing\ninvoice"),
+            poll => panic!("expected ready batch, got {:?}", poll),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serenity::{
+    builder::CreateComponents,
+    model::{id::MessageId, interactions::message_component::ButtonStyle},
+};
+
+/// How many categories there can be, as many buttons as fit in a row.
+pub const MAX_CATEGORIES: usize = 5;
+
+/// How long a category's name can be, so its button's custom ID stays within Discord's limit.
+pub const MAX_LEN: usize = 64;
+
+/// What the buttons call rooms from before categories were set up.
+pub const UNCATEGORIZED_LABEL: &str = "Other";
+
+const PREFIX: &str = "category";
+
+/// The categories users pick from for their threads, stored comma-separated.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Categories(pub Vec<String>);
+
+impl Display for Categories {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(","))
+    }
+}
+
+impl FromStr for Categories {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let categories: Vec<_> = s
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(
+                |name| match name.contains(':') || name.chars().count() > MAX_LEN {
+                    true => Err(format!("invalid category `{}`", name)),
+                    false => Ok(name.to_owned()),
+                },
+            )
+            .collect::<Result<_, _>>()?;
+
+        if categories.len() > MAX_CATEGORIES {
+            return Err(format!("at most {} categories are allowed", MAX_CATEGORIES));
+        }
+        Ok(Self(categories))
+    }
+}
+
+/// A user's pick of which category a DM is about, as encoded in the custom ID of its button.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Choice {
+    /// `None` for rooms without a category.
+    pub category: Option<String>,
+    /// The DM waiting to be forwarded.
+    pub message: MessageId,
+}
+
+impl Choice {
+    pub fn parse(custom_id: &str) -> Option<Self> {
+        let mut parts = custom_id.split(':');
+        if parts.next()? != PREFIX {
+            return None;
+        }
+
+        let category = match parts.next()? {
+            "" => None,
+            category => Some(category.to_owned()),
+        };
+        let message = MessageId(parts.next()?.parse().ok()?);
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self { category, message })
+    }
+
+    pub fn custom_id(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            PREFIX,
+            self.category.as_deref().unwrap_or_default(),
+            self.message
+        )
+    }
+
+    /// Adds a button for each of `categories`, to forward `message` to. Only the first
+    /// [`MAX_CATEGORIES`] fit, which only matters for users with rooms from before the
+    /// categories last changed.
+    pub fn buttons<'a>(
+        categories: &[Option<String>],
+        message: MessageId,
+        components: &'a mut CreateComponents,
+    ) -> &'a mut CreateComponents {
+        components.create_action_row(|row| {
+            for category in categories.iter().take(MAX_CATEGORIES) {
+                let choice = Self {
+                    category: category.clone(),
+                    message,
+                };
+                row.create_button(|button| {
+                    button
+                        .custom_id(choice.custom_id())
+                        .label(category.as_deref().unwrap_or(UNCATEGORIZED_LABEL))
+                        .style(ButtonStyle::Primary)
+                });
+            }
+            row
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Categories, Choice};
+
+    #[test]
+    fn categories_roundtrip() {
+        let categories: Categories = "billing, technical,".parse().unwrap();
+        assert_eq!(
+            categories,
+            Categories(vec!["billing".into(), "technical".into()])
+        );
+        assert_eq!(categories.to_string(), "billing,technical");
+        assert_eq!("".parse::<Categories>().unwrap(), Categories::default());
+
+        assert!("bill:ing".parse::<Categories>().is_err());
+        assert!("a,b,c,d,e,f".parse::<Categories>().is_err());
+    }
+
+    #[test]
+    fn choice_custom_id() {
+        let choice = Choice {
+            category: Some("billing".into()),
+            message: 123.into(),
+        };
+        assert_eq!(choice.custom_id(), "category:billing:123");
+        assert_eq!(Choice::parse(&choice.custom_id()), Some(choice));
+
+        let uncategorized = Choice {
+            category: None,
+            message: 123.into(),
+        };
+        assert_eq!(uncategorized.custom_id(), "category::123");
+        assert_eq!(Choice::parse("category::123"), Some(uncategorized));
+
+        assert_eq!(Choice::parse("category:billing:abc"), None);
+        assert_eq!(Choice::parse("page:list:0:0"), None);
+    }
+}
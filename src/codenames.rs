@@ -0,0 +1,55 @@
+/// Bounds on the length of codenames picked by users.
+pub const MIN_LEN: usize = 2;
+pub const MAX_LEN: usize = 32;
+
+/// Discord's limit on the length of a thread's name.
+pub const THREAD_NAME_MAX_LEN: usize = 100;
+
+/// Characters that would break codenames shown in `inline code`.
+const REJECTED: [char; 2] = ['`', '\\'];
+
+/// Makes a codename safe to use in thread names and messages: control characters such as
+/// newlines become spaces, runs of whitespace are collapsed and anything past [`MAX_LEN`] is
+/// cut off.
+pub fn sanitize(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !REJECTED.contains(c))
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate(&collapsed, MAX_LEN).trim_end().to_owned()
+}
+
+/// Cuts `s` off after `max` characters.
+pub fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((end, _)) => &s[..end],
+        None => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize, truncate, MAX_LEN};
+
+    #[test]
+    fn sanitize_codenames() {
+        assert_eq!(sanitize("quiet owl"), "quiet owl");
+        assert_eq!(sanitize("  quiet\n\towl \u{7}"), "quiet owl");
+        assert_eq!(sanitize("`quiet` owl\\"), "quiet owl");
+        assert_eq!(sanitize("\n\n"), "");
+
+        let long = sanitize(&"owl ".repeat(20));
+        assert!(long.chars().count() <= MAX_LEN);
+        assert!(!long.ends_with(' '));
+        assert_eq!(sanitize(&"🦉".repeat(40)).chars().count(), MAX_LEN);
+    }
+
+    #[test]
+    fn truncate_chars() {
+        assert_eq!(truncate("quiet owl", 5), "quiet");
+        assert_eq!(truncate("quiet owl", 100), "quiet owl");
+        assert_eq!(truncate("äöü", 2), "äö");
+    }
+}
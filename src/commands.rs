@@ -0,0 +1,1097 @@
+//! Definitions of the bot's slash commands.
+
+use serenity::{
+    builder::CreateApplicationCommands,
+    model::{
+        channel::ChannelType,
+        interactions::application_command::{ApplicationCommandOptionType, ApplicationCommandType},
+    },
+};
+
+use crate::{
+    COALESCE_WINDOW_MAX_MS, COALESCE_WINDOW_MIN_MS, MAINTENANCE_INTERVAL_MAX, PRIORITIES,
+    TRANSCRIPT_LIMIT_MAX, TRANSCRIPT_RETENTION_MAX_DAYS,
+};
+
+/// The commands registered by default, which embedders can extend or trim with [`remove`] before
+/// handing them to [`Bot::with_commands`](crate::Bot::with_commands).
+pub fn default_commands() -> CreateApplicationCommands {
+    let mut cmds = CreateApplicationCommands::default();
+    cmds.create_application_command(|cmd| {
+        cmd.name("block")
+            .description("Block a user from using the bot.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Must be an exact match.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("unblock-all")
+            .description("Unblock every member who has the block role, after confirming.")
+            .kind(ApplicationCommandType::ChatInput)
+    })
+    .create_application_command(|cmd| {
+        cmd.name("unblock")
+            .description("Unblock a user from using the bot.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Must be an exact match.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("restore")
+                    .description(
+                        "Whether to give back roles removed by the block. Defaults to true.",
+                    )
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("blockstrip")
+            .description("Manage roles removed from users when they're blocked.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("add")
+                    .description("Remove a role from users when they're blocked.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("role")
+                            .description("The role to be removed.")
+                            .kind(ApplicationCommandOptionType::Role)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("remove")
+                    .description("Stop removing a role from users when they're blocked.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("role")
+                            .description("The role to be kept.")
+                            .kind(ApplicationCommandOptionType::Role)
+                            .required(true)
+                    })
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("blocklist")
+            .description("List blocked users.")
+            .kind(ApplicationCommandType::ChatInput)
+    })
+    .create_application_command(|cmd| {
+        cmd.name("blockrole")
+            .description("Manage the role given to blocked users.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Set the role given to blocked users.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("role")
+                            .description("The role to be used.")
+                            .kind(ApplicationCommandOptionType::Role)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Unset the block role.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("broadcast")
+            .description("Send a message to every user with an open thread.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("message")
+                    .description("The message to be sent.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("coalesce")
+            .description("Manage whether rapid messages from users are combined.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Combine messages sent in quick succession.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("milliseconds")
+                            .description("How long to wait for more messages.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .min_int_value(COALESCE_WINDOW_MIN_MS)
+                            .max_int_value(COALESCE_WINDOW_MAX_MS)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Forward messages one by one.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("codename")
+            .description("Manage whether users can pick their own codename.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Let users pick a codename in their first message.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("command")
+                            .description("The command users type, `!codename` by default.")
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Always generate codenames.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("deadletters")
+            .description("Show recent messages that failed to forward.")
+            .kind(ApplicationCommandType::ChatInput)
+    })
+    .create_application_command(|cmd| {
+        cmd.name("export")
+            .description("Export configuration and rooms as JSON. Owner only.")
+            .kind(ApplicationCommandType::ChatInput)
+    })
+    .create_application_command(|cmd| {
+        cmd.name("filter")
+            .description("Manage patterns redacted from users' messages.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("add")
+                    .description("Redact matches of a pattern.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("pattern")
+                            .description("The regular expression to be redacted.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("remove")
+                    .description("Stop redacting a pattern.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("id")
+                            .description("The filter's number, as shown by `/filter list`.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("list")
+                    .description("List all filters.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("mentions")
+            .description("Manage the role pinged when users reply to a quiet thread.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Set the role pinged when users reply to a quiet thread.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("role")
+                            .description("The role to be pinged.")
+                            .kind(ApplicationCommandOptionType::Role)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Stop pinging anyone on replies.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("officehours")
+            .description("Manage the hours during which staff are available.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Set the daily office hours.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("start")
+                            .description("The opening time, e.g. `09:00`.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("end")
+                            .description("The closing time, e.g. `17:00`.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("timezone")
+                            .description("The timezone, e.g. `Europe/Berlin`.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("message")
+                            .description("The reply sent to users outside of office hours.")
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("hold")
+                            .description("Refuse to open threads outside of office hours.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Remove the office hours.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("refresh-commands")
+            .description("Re-register the bot's commands. Owner only.")
+            .kind(ApplicationCommandType::ChatInput)
+    })
+    .create_application_command(|cmd| {
+        cmd.name("dbmaintenance")
+            .description("Clean up old data and compact the database. Owner only.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("run")
+                    .description("Run maintenance right away.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+            .create_option(|opt| {
+                opt.name("interval")
+                    .description("Set how often maintenance runs.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("hours")
+                            .description("Hours between runs.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .min_int_value(1)
+                            .max_int_value(MAINTENANCE_INTERVAL_MAX)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("transcripts")
+                    .description("Set how long transcripts are kept.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("days")
+                            .description("Days to keep transcripts for, forever if left out.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .min_int_value(1)
+                            .max_int_value(TRANSCRIPT_RETENTION_MAX_DAYS)
+                    })
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("maintenance")
+            .description("Stop taking new threads for a while.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("on")
+                    .description("Stop opening new threads. Open threads keep working.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("message")
+                            .description("What users trying to open a thread are told.")
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("off")
+                    .description("Open new threads again.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("setconfig")
+            .description("Set a config key directly, e.g. one without its own command.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("key")
+                    .description("The config key, e.g. `close_dm`.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("value")
+                    .description("The new value. Leave out to unset the key.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("getconfig")
+            .description("Show the stored value of a config key.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("key")
+                    .description("The config key, e.g. `close_dm`.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("rebind-guild")
+            .description("Move the bot to another server it's a member of.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("guild")
+                    .description("The ID of the server.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("import")
+            .description("Replace configuration and rooms with a backup. Owner only.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("file")
+                    .description("A file produced by `/export`.")
+                    .kind(ApplicationCommandOptionType::Attachment)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("inbox")
+            .description("Manage the channel threads will be added to.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Set the channel threads will be added to.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("channel")
+                            .description("The channel to be used. Must allow threads.")
+                            .kind(ApplicationCommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Text, ChannelType::News])
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Unset the inbox channel.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("migrate-inbox")
+            .description("Switch to a new inbox, moving open threads there.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("channel")
+                    .description("The new inbox. Must allow threads.")
+                    .kind(ApplicationCommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Text, ChannelType::News])
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("move")
+                    .description("Whether to recreate open threads in the new inbox. Defaults to true.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("maxthreads")
+            .description("Manage how many open threads the inbox takes.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Limit how many open threads the inbox takes.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("max")
+                            .description("How many threads can be open in the inbox.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .min_int_value(1)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("overflow")
+                            .description("Where new threads go once the inbox is full. Otherwise, users are turned away.")
+                            .kind(ApplicationCommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Text, ChannelType::News])
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Let the inbox take any number of threads.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("remind")
+            .description("Get pinged in a thread later.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("duration")
+                    .description("How long until the reminder, e.g. `30m`, `2h` or `1d`.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("snooze")
+            .description("Hide a thread from `/list` for a while.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("duration")
+                    .description("How long to hide it, e.g. `30m`, `2h` or `1d`.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+            .create_option(|opt| {
+                opt.name("ping")
+                    .description("Get pinged in the thread once it's back.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("merge")
+            .description("Merge a user's thread into another of theirs from a different category.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("source")
+                    .description("The codename of the thread to merge and archive.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("target")
+                    .description("The codename of the thread to keep.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("unsnooze")
+            .description("Show a snoozed thread in `/list` again.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("schedule")
+            .description("Manage messages sent to a thread's user later.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("add")
+                    .description("Send a message to the user later.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("duration")
+                            .description("How long until it's sent, e.g. `30m`, `2h` or `1d`.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("message")
+                            .description("The message to send.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("codename")
+                            .description("The codename. Defaults to the current thread.")
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("list")
+                    .description("List a thread's scheduled messages.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("codename")
+                            .description("The codename. Defaults to the current thread.")
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("cancel")
+                    .description("Cancel a scheduled message.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("id")
+                            .description("The scheduled message's ID, see `/schedule list`.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(true)
+                    })
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("forget")
+            .description("Delete everything stored about a user.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("user")
+                    .description("The user to forget.")
+                    .kind(ApplicationCommandOptionType::User)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("footer")
+            .description("Choose whether the bot's responses have a footer.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("show")
+                    .description("Whether to show the footer.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("analytics")
+            .description("Choose whether closed threads are counted for `/report`.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("record")
+                    .description("Whether to count closed threads.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("report")
+            .description("Summarize how many threads came in and how fast staff replied.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("period")
+                    .description("How far back to look, e.g. `7d` or `4w`.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("presence")
+            .description("Choose whether the bot's status shows how many threads are open.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("show")
+                    .description("Whether to show the thread count.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("reopensummary")
+            .description("Choose whether reopened threads start with a summary of what was missed.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("show")
+                    .description("Whether to post the summary.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("revealusername")
+            .description("Choose whether thread names include the user's username.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("show")
+                    .description("Whether to add the username after the codename.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("commandrole")
+            .description("Manage roles that are required to use commands.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Require a role for a command instead of its usual permissions.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("command")
+                            .description("The command's name, e.g. `close`.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("role")
+                            .description("The role members need.")
+                            .kind(ApplicationCommandOptionType::Role)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Go back to a command's usual permissions.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("command")
+                            .description("The command's name, e.g. `close`.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("triggeremoji")
+            .description("Manage reactions that close or block threads.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("React to a thread's message in the inbox with an emoji to act on it.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("action")
+                            .description("What the reaction does.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("close", "close")
+                            .add_string_choice("block", "block")
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("emoji")
+                            .description("The emoji, e.g. 🔒.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Stop a reaction from acting on threads.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("action")
+                            .description("What the reaction did.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("close", "close")
+                            .add_string_choice("block", "block")
+                            .required(true)
+                    })
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("history")
+            .description("List a user's closed threads, or get one of their transcripts.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("user")
+                    .description("The user whose threads to show.")
+                    .kind(ApplicationCommandOptionType::User)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("transcript")
+                    .description("The number of a transcript to get, as listed.")
+                    .kind(ApplicationCommandOptionType::Integer)
+                    .min_int_value(1)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("resend")
+            .description("Try again to deliver the last message that didn't reach a thread's user.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("list")
+            .description("List open threads, most urgent first.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("snoozed")
+                    .description("List snoozed threads instead.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("priority")
+            .description("Set how urgent a thread is.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Must be an exact match.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("priority")
+                    .description("The new priority.")
+                    .kind(ApplicationCommandOptionType::Integer)
+                    .required(true);
+                for (value, name) in PRIORITIES.iter().enumerate() {
+                    opt.add_int_choice(name, value as i32);
+                }
+                opt
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("transcriptlimit")
+            .description("Manage how many messages transcripts of closed threads keep.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Set how many of a thread's most recent messages are kept.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("messages")
+                            .description("The number of messages.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .min_int_value(1)
+                            .max_int_value(TRANSCRIPT_LIMIT_MAX)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Go back to the default limit.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("attach")
+            .description("Turn an existing thread into a user's thread.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("user")
+                    .description("The user whose messages go to the thread.")
+                    .kind(ApplicationCommandOptionType::User)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("thread")
+                    .description("The thread. Defaults to the current one.")
+                    .kind(ApplicationCommandOptionType::Channel)
+                    .channel_types(&[
+                        ChannelType::PublicThread,
+                        ChannelType::PrivateThread,
+                        ChannelType::NewsThread,
+                    ])
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("set-codename")
+            .description("Give a user a codename that's kept for all their threads.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("user")
+                    .description("The user to name.")
+                    .kind(ApplicationCommandOptionType::User)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("Their new codename.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("claim")
+            .description("Take over a thread, so only your messages reach the user.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("unclaim")
+            .description("Release a thread, so all staff messages reach the user again.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("reassign-from")
+            .description("Release or hand over all threads claimed by someone, e.g. when they leave.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("user")
+                    .description("Whose threads to reassign.")
+                    .kind(ApplicationCommandOptionType::User)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("to")
+                    .description("Who should claim them instead. Leaves them unclaimed otherwise.")
+                    .kind(ApplicationCommandOptionType::User)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("mute-notifications")
+            .description("Toggle whether you're pinged when users reply to a quiet thread.")
+            .kind(ApplicationCommandType::ChatInput)
+    })
+    .create_application_command(|cmd| {
+        cmd.name("snippet")
+            .description("Manage canned responses sent with `/reply`.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("add")
+                    .description("Add a snippet.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("name")
+                            .description("The name to send it by.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("content")
+                            .description("The response. `{codename}` and `{user}` are filled in.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("edit")
+                    .description("Change a snippet's response.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("name")
+                            .description("The snippet's name.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("content")
+                            .description(
+                                "The new response. `{codename}` and `{user}` are filled in.",
+                            )
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("remove")
+                    .description("Remove a snippet.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("name")
+                            .description("The snippet's name.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("emoji")
+                    .description("Send a snippet by reacting to a thread's message in the inbox.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("name")
+                            .description("The snippet's name.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_sub_option(|sub| {
+                        sub.name("emoji")
+                            .description("The emoji, e.g. ✅. Leave out to stop reacting with one.")
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("list")
+                    .description("List all snippets.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("reply")
+            .description("Send a snippet to the user of this thread.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("snippet")
+                    .description("The snippet's name.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("pause")
+            .description("Stop sending messages in a thread to the user for now.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+            .create_option(|opt| {
+                opt.name("drop")
+                    .description("Drop messages sent while paused instead of sending them on `/resume`.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("resume")
+            .description("Send messages in a thread to the user again.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Defaults to the current thread.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("threadlink")
+            .description("Get a link to a thread.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Must be an exact match.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("close")
+            .description("Close this thread and forget the attached user.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("codename")
+                    .description("The codename. Must be an exact match.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("reason")
+                    .description("The reason, shown to the user if a close message is set.")
+                    .kind(ApplicationCommandOptionType::String)
+            })
+            .create_option(|opt| {
+                opt.name("delete")
+                    .description("Delete the thread instead of archiving it, once confirmed.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("preview")
+            .description("See a message users get, filled in with sample values.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("message")
+                    .description("The message to preview.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+                    .add_string_choice("greeting", "greeting")
+                    .add_string_choice("close", "close")
+                    .add_string_choice("blocked", "blocked")
+                    .add_string_choice("out of hours", "out-of-hours")
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("close-all-inactive")
+            .description("Close every thread that has been quiet for a while.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("idle")
+                    .description("How long threads must have been quiet, e.g. `7d`.")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            })
+            .create_option(|opt| {
+                opt.name("dry_run")
+                    .description("Only list the threads that would be closed. Defaults to true.")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            })
+    })
+    .create_application_command(|cmd| {
+        cmd.name("closedm")
+            .description("Manage the message sent to users when their thread is closed.")
+            .kind(ApplicationCommandType::ChatInput)
+            .create_option(|opt| {
+                opt.name("set")
+                    .description("Set the close message. Supports {codename} and {reason}.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+                    .create_sub_option(|sub| {
+                        sub.name("template")
+                            .description("The message to be sent.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|opt| {
+                opt.name("unset")
+                    .description("Stop messaging users when their thread is closed.")
+                    .kind(ApplicationCommandOptionType::SubCommand)
+            })
+    });
+    cmds
+}
+
+/// Commands whose responses are only shown to whoever used them by default, as they're
+/// informational or sensitive.
+pub const EPHEMERAL: [&str; 8] = [
+    "blocklist",
+    "deadletters",
+    "export",
+    "getconfig",
+    "history",
+    "list",
+    "preview",
+    "report",
+];
+
+/// Removes the command called `name` from `cmds`, if present.
+pub fn remove(cmds: &mut CreateApplicationCommands, name: &str) {
+    cmds.0.retain(|cmd| cmd["name"] != name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_commands, remove, EPHEMERAL};
+
+    #[test]
+    fn remove_commands() {
+        let mut cmds = default_commands();
+        let count = cmds.0.len();
+        remove(&mut cmds, "broadcast");
+        remove(&mut cmds, "no-such-command");
+        assert_eq!(cmds.0.len(), count - 1);
+        assert!(cmds.0.iter().all(|cmd| cmd["name"] != "broadcast"));
+    }
+
+    #[test]
+    fn ephemeral_commands_exist() {
+        let cmds = default_commands();
+        for name in EPHEMERAL {
+            assert!(cmds.0.iter().any(|cmd| cmd["name"] == name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serenity::{
+    builder::CreateComponents,
+    model::{id::UserId, interactions::message_component::ButtonStyle},
+};
+
+use crate::custom_id;
+
+/// How long a confirmation button keeps working.
+pub const CONFIRM_TIMEOUT_MINUTES: i64 = 5;
+
+const PREFIX: &str = "confirm";
+
+/// An action waiting for confirmation, as encoded in the custom ID of its button.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Confirmation {
+    /// The command whose action is confirmed.
+    pub action: String,
+    /// Who asked for the action, the only one who may confirm it.
+    pub user: UserId,
+    pub created_at: DateTime<Utc>,
+    /// What the action applies to, e.g. the thread `/close` deletes.
+    pub target: Option<u64>,
+}
+
+impl Confirmation {
+    pub fn new(action: &str, user: UserId) -> Self {
+        Self {
+            action: action.to_owned(),
+            user,
+            created_at: custom_id::now(),
+            target: None,
+        }
+    }
+
+    pub fn with_target(self, target: u64) -> Self {
+        Self {
+            target: Some(target),
+            ..self
+        }
+    }
+
+    pub fn parse(custom_id: &str) -> Option<Self> {
+        let (fields, created_at) = custom_id::decode(PREFIX, custom_id)?;
+        let (action, user, target) = match fields[..] {
+            [action, user] => (action, user, None),
+            [action, user, target] => (action, user, Some(target.parse().ok()?)),
+            _ => return None,
+        };
+
+        Some(Self {
+            action: action.to_owned(),
+            user: UserId(user.parse().ok()?),
+            created_at,
+            target,
+        })
+    }
+
+    pub fn custom_id(&self) -> String {
+        let mut fields = vec![self.action.clone(), self.user.to_string()];
+        fields.extend(self.target.map(|target| target.to_string()));
+        custom_id::encode(PREFIX, &fields, self.created_at)
+    }
+
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        custom_id::expired(self.created_at, now, CONFIRM_TIMEOUT_MINUTES)
+    }
+
+    /// Adds the button confirming the action.
+    pub fn button<'a>(&self, components: &'a mut CreateComponents) -> &'a mut CreateComponents {
+        components.create_action_row(|row| {
+            row.create_button(|button| {
+                button
+                    .custom_id(self.custom_id())
+                    .label("Confirm")
+                    .style(ButtonStyle::Danger)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::Confirmation;
+
+    #[test]
+    fn confirmation_custom_id() {
+        let confirmation = Confirmation {
+            action: "unblock-all".into(),
+            user: 42.into(),
+            created_at: Utc.timestamp_opt(1_650_000_000, 0).unwrap(),
+            target: None,
+        };
+        assert_eq!(
+            confirmation.custom_id(),
+            "confirm:unblock-all:42:1650000000"
+        );
+        assert_eq!(
+            Confirmation::parse(&confirmation.custom_id()),
+            Some(confirmation)
+        );
+
+        let targeted = Confirmation::new("close", 42.into()).with_target(7);
+        assert!(targeted.custom_id().starts_with("confirm:close:42:7:"));
+        assert_eq!(Confirmation::parse(&targeted.custom_id()), Some(targeted));
+
+        assert_eq!(
+            Confirmation::parse("confirm:unblock-all:me:1650000000"),
+            None
+        );
+        assert_eq!(
+            Confirmation::parse("confirm:close:42:seven:1650000000"),
+            None
+        );
+        assert_eq!(Confirmation::parse("page:list:2:1650000000"), None);
+    }
+
+    #[test]
+    fn confirmation_expiry() {
+        let confirmation = Confirmation::new("unblock-all", 42.into());
+        assert!(!confirmation.expired(Utc::now()));
+        assert!(confirmation.expired(Utc::now() + Duration::hours(1)));
+    }
+}
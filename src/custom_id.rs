@@ -0,0 +1,63 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+/// The current time, as precise as custom IDs can hold it.
+pub fn now() -> DateTime<Utc> {
+    // custom IDs only keep whole seconds
+    Utc.timestamp_opt(Utc::now().timestamp(), 0).unwrap()
+}
+
+/// Encodes a button's state as `prefix:field:...:created_at`.
+pub fn encode(prefix: &str, fields: &[String], created_at: DateTime<Utc>) -> String {
+    let mut custom_id = prefix.to_owned();
+    for field in fields {
+        custom_id.push(':');
+        custom_id.push_str(field);
+    }
+    format!("{}:{}", custom_id, created_at.timestamp())
+}
+
+/// Splits a custom ID made by [`encode`] with `prefix` back into its fields and when it was
+/// created, or returns `None` if it belongs to something else or is malformed.
+pub fn decode<'a>(prefix: &str, custom_id: &'a str) -> Option<(Vec<&'a str>, DateTime<Utc>)> {
+    let mut fields: Vec<_> = custom_id.split(':').collect();
+    if fields.len() < 2 || fields.remove(0) != prefix {
+        return None;
+    }
+
+    let created_at = Utc.timestamp_opt(fields.pop()?.parse().ok()?, 0).single()?;
+    Some((fields, created_at))
+}
+
+/// Whether a button created at `created_at` stopped working by `now`.
+pub fn expired(created_at: DateTime<Utc>, now: DateTime<Utc>, timeout_minutes: i64) -> bool {
+    now - created_at > Duration::minutes(timeout_minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{decode, encode, expired, now};
+
+    #[test]
+    fn custom_id_roundtrip() {
+        let created_at = Utc.timestamp_opt(1_650_000_000, 0).unwrap();
+        let custom_id = encode("page", &["list".into(), "2".into()], created_at);
+        assert_eq!(custom_id, "page:list:2:1650000000");
+        assert_eq!(
+            decode("page", &custom_id),
+            Some((vec!["list", "2"], created_at))
+        );
+
+        assert_eq!(decode("confirm", &custom_id), None);
+        assert_eq!(decode("page", "page:list:soon"), None);
+        assert_eq!(decode("page", "page"), None);
+    }
+
+    #[test]
+    fn custom_id_expiry() {
+        let created_at = now();
+        assert!(!expired(created_at, Utc::now(), 5));
+        assert!(expired(created_at, Utc::now() + Duration::hours(1), 5));
+    }
+}
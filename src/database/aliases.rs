@@ -0,0 +1,89 @@
+use crate::error::Result;
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+
+/// A codename staff gave a user with `/set-codename`, reused for all their future threads.
+pub struct Alias;
+
+impl Alias {
+    /// Gives the user `codename`, replacing any codename they had before.
+    pub async fn set(pool: &SqlitePool, user_id: UserId, codename: &str) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        sqlx::query!(
+            "INSERT INTO aliases (user_id, codename) VALUES (?, ?)
+            ON CONFLICT (user_id) DO UPDATE SET codename = excluded.codename",
+            temp,
+            codename
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    pub async fn get(pool: &SqlitePool, user_id: UserId) -> Result<Option<String>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        Ok(
+            sqlx::query!("SELECT codename FROM aliases WHERE user_id = ?", temp)
+                .fetch_optional(pool)
+                .await
+                .map_err(anyhow::Error::from)?
+                .map(|r| r.codename),
+        )
+    }
+
+    /// Returns the user `codename` belongs to, if any.
+    pub async fn owner(pool: &SqlitePool, codename: &str) -> Result<Option<UserId>> {
+        Ok(
+            sqlx::query!("SELECT user_id FROM aliases WHERE codename = ?", codename)
+                .fetch_optional(pool)
+                .await
+                .map_err(anyhow::Error::from)?
+                .map(|r| {
+                    r.user_id
+                        .parse::<u64>()
+                        .expect("got malformed Alias from database")
+                        .into()
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::Alias;
+
+    #[tokio::test]
+    async fn alias_crud() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        Alias::set(&pool, 1.into(), "night owl").await.unwrap();
+        assert!(Alias::set(&pool, 2.into(), "night owl").await.is_err());
+
+        // Get
+        assert_eq!(
+            Alias::get(&pool, 1.into()).await.unwrap().as_deref(),
+            Some("night owl")
+        );
+        assert_eq!(Alias::get(&pool, 2.into()).await.unwrap(), None);
+        assert_eq!(
+            Alias::owner(&pool, "night owl").await.unwrap(),
+            Some(1.into())
+        );
+
+        // Update
+        Alias::set(&pool, 1.into(), "early bird").await.unwrap();
+        assert_eq!(Alias::owner(&pool, "night owl").await.unwrap(), None);
+        assert_eq!(
+            Alias::get(&pool, 1.into()).await.unwrap().as_deref(),
+            Some("early bird")
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use std::{collections::HashSet, result::Result as StdResult};
+
+use crate::{
+    database::{config, rooms::Pause},
+    error::Result,
+};
+use chrono::{LocalResult, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+const VERSION: u32 = 1;
+
+/// A portable copy of the bot's configuration and open rooms.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Backup {
+    version: u32,
+    config: Vec<ConfigEntry>,
+    rooms: Vec<RoomEntry>,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+#[serde(deny_unknown_fields)]
+struct ConfigEntry {
+    key: String,
+    value: String,
+}
+
+#[derive(Serialize, Deserialize, FromRow)]
+#[serde(deny_unknown_fields)]
+struct RoomEntry {
+    room_id: i64,
+    codename: String,
+    channel_id: String,
+    user_id: String,
+    /// Left out by backups from before categories.
+    category: Option<String>,
+    // the rest are left out by backups from before they were included
+    /// Falls back to the configured inbox, like the migration that added it.
+    #[serde(default)]
+    inbox_id: Option<String>,
+    /// Falls back to the time of the restore, so restored threads aren't all inactive.
+    #[serde(default = "now")]
+    last_activity_at: i64,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    priority: i64,
+    #[serde(default)]
+    claimed_by: Option<String>,
+    #[serde(default)]
+    pause: Option<String>,
+    #[serde(default)]
+    snoozed_until: Option<i64>,
+    #[serde(default)]
+    opened_at: Option<i64>,
+    #[serde(default)]
+    first_staff_reply_at: Option<i64>,
+    #[serde(default)]
+    sla_alerted: bool,
+}
+
+fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+impl Backup {
+    pub async fn dump(pool: &SqlitePool) -> Result<Self> {
+        let config = sqlx::query_as!(ConfigEntry, "SELECT * FROM config ORDER BY key")
+            .fetch_all(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        let rooms = sqlx::query_as!(
+            RoomEntry,
+            "SELECT room_id, codename, channel_id, user_id, category, inbox_id, last_activity_at,
+            archived, priority, claimed_by, pause, snoozed_until, opened_at, first_staff_reply_at,
+            sla_alerted
+            FROM rooms ORDER BY room_id"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        Ok(Self {
+            version: VERSION,
+            config,
+            rooms,
+        })
+    }
+
+    /// Checks the backup can be restored without leaving malformed data behind.
+    pub fn validate(&self) -> StdResult<(), String> {
+        if self.version != VERSION {
+            return Err(format!("unsupported backup version {}", self.version));
+        }
+
+        for entry in &self.config {
+            config::validate(&entry.key, &entry.value)?;
+        }
+
+        let (mut ids, mut codenames, mut channels, mut users) = (
+            HashSet::new(),
+            HashSet::new(),
+            HashSet::new(),
+            HashSet::new(),
+        );
+        for room in &self.rooms {
+            let snowflakes = [
+                Some(&room.channel_id),
+                Some(&room.user_id),
+                room.inbox_id.as_ref(),
+                room.claimed_by.as_ref(),
+            ];
+            if !snowflakes
+                .into_iter()
+                .flatten()
+                .all(|id| id.parse::<u64>().is_ok())
+            {
+                return Err(format!("room `{}` has a malformed ID", room.codename));
+            }
+
+            let timestamps_valid = [
+                Some(room.last_activity_at),
+                room.snoozed_until,
+                room.opened_at,
+                room.first_staff_reply_at,
+            ]
+            .into_iter()
+            .flatten()
+            .all(|timestamp| matches!(Utc.timestamp_opt(timestamp, 0), LocalResult::Single(_)));
+            if !timestamps_valid {
+                return Err(format!(
+                    "room `{}` has a malformed timestamp",
+                    room.codename
+                ));
+            }
+
+            if let Some(pause) = &room.pause {
+                pause
+                    .parse::<Pause>()
+                    .map_err(|err| format!("room `{}` has {}", room.codename, err))?;
+            }
+
+            if !ids.insert(room.room_id)
+                || !codenames.insert(&room.codename)
+                || !channels.insert(&room.channel_id)
+                || !users.insert((&room.user_id, &room.category))
+            {
+                return Err(format!("room `{}` is a duplicate", room.codename));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces all configuration and rooms with the contents of the backup.
+    pub async fn restore(&self, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(anyhow::Error::from)?;
+
+        sqlx::query!("DELETE FROM rooms")
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?;
+        sqlx::query!("DELETE FROM config")
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        for entry in &self.config {
+            sqlx::query!(
+                "INSERT INTO config (key, value) VALUES (?, ?)",
+                entry.key,
+                entry.value
+            )
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?;
+        }
+
+        for room in &self.rooms {
+            sqlx::query!(
+                "INSERT INTO rooms (room_id, codename, channel_id, user_id, category, inbox_id,
+                last_activity_at, archived, priority, claimed_by, pause, snoozed_until, opened_at,
+                first_staff_reply_at, sla_alerted)
+                VALUES (?, ?, ?, ?, ?, IFNULL(?, (SELECT value FROM config WHERE key = 'inbox')),
+                ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                room.room_id,
+                room.codename,
+                room.channel_id,
+                room.user_id,
+                room.category,
+                room.inbox_id,
+                room.last_activity_at,
+                room.archived,
+                room.priority,
+                room.claimed_by,
+                room.pause,
+                room.snoozed_until,
+                room.opened_at,
+                room.first_staff_reply_at,
+                room.sla_alerted
+            )
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?;
+        }
+
+        tx.commit().await.map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serenity::model::id::{ChannelId, RoleId, UserId};
+    use sqlx::SqlitePool;
+
+    use super::Backup;
+    use crate::database::{
+        config::{Blockrole, Config},
+        rooms::{Pause, Room},
+    };
+
+    #[tokio::test]
+    async fn backup_roundtrip() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let config = Config::new(pool.clone());
+        config.set(Blockrole, RoleId(123)).await.unwrap();
+        let mut room = Room::new(&pool, "accurate wren".into(), ChannelId(1), UserId(2))
+            .await
+            .unwrap();
+        let snoozed_until = Utc.timestamp_opt(2_000_000_000, 0).unwrap();
+        room.set_inbox_id(&pool, ChannelId(3)).await.unwrap();
+        room.set_archived(&pool, true).await.unwrap();
+        room.set_priority(&pool, 2).await.unwrap();
+        room.set_claimed_by(&pool, Some(UserId(4))).await.unwrap();
+        room.set_pause(&pool, Some(Pause::Queue)).await.unwrap();
+        room.set_snoozed_until(&pool, Some(snoozed_until))
+            .await
+            .unwrap();
+        room.set_first_staff_reply_at(&pool, Utc::now())
+            .await
+            .unwrap();
+        room.set_sla_alerted(&pool).await.unwrap();
+
+        // Dump
+        let json = serde_json::to_string(&Backup::dump(&pool).await.unwrap()).unwrap();
+
+        // Diverge from the backup
+        config.set(Blockrole, RoleId(321)).await.unwrap();
+        Room::get_by_user(&pool, UserId(2))
+            .await
+            .unwrap()
+            .unwrap()
+            .delete(&pool)
+            .await
+            .unwrap();
+
+        // Restore
+        let backup: Backup = serde_json::from_str(&json).unwrap();
+        backup.validate().unwrap();
+        backup.restore(&pool).await.unwrap();
+        assert_eq!(config.get(Blockrole).await.unwrap(), Some(RoleId(123)));
+        let restored = Room::get_by_user(&pool, UserId(2)).await.unwrap().unwrap();
+        assert_eq!(restored.codename, "accurate wren");
+        assert_eq!(restored.inbox_id, Some(ChannelId(3)));
+        assert_eq!(
+            restored.last_activity_at.timestamp(),
+            room.last_activity_at.timestamp()
+        );
+        assert!(restored.archived);
+        assert_eq!(restored.priority, 2);
+        assert_eq!(restored.claimed_by, Some(UserId(4)));
+        assert_eq!(restored.pause, Some(Pause::Queue));
+        assert_eq!(restored.snoozed_until, Some(snoozed_until));
+        assert_eq!(
+            restored.opened_at.map(|at| at.timestamp()),
+            room.opened_at.map(|at| at.timestamp())
+        );
+        assert_eq!(
+            restored.first_staff_reply_at.map(|at| at.timestamp()),
+            room.first_staff_reply_at.map(|at| at.timestamp())
+        );
+        assert!(restored.sla_alerted);
+    }
+
+    #[test]
+    fn backup_validate() {
+        let parse = |json: &str| serde_json::from_str::<Backup>(json);
+
+        let valid = r#"{"version":1,"config":[{"key":"inbox","value":"1"}],"rooms":[]}"#;
+        assert!(parse(valid).unwrap().validate().is_ok());
+
+        let bad_key = r#"{"version":1,"config":[{"key":"nope","value":"1"}],"rooms":[]}"#;
+        assert!(parse(bad_key).unwrap().validate().is_err());
+
+        let duplicate = r#"{"version":1,"config":[],"rooms":[
+            {"room_id":1,"codename":"a","channel_id":"1","user_id":"1"},
+            {"room_id":2,"codename":"b","channel_id":"2","user_id":"1"}]}"#;
+        assert!(parse(duplicate).unwrap().validate().is_err());
+
+        let categorized = r#"{"version":1,"config":[],"rooms":[
+            {"room_id":1,"codename":"a","channel_id":"1","user_id":"1"},
+            {"room_id":2,"codename":"b","channel_id":"2","user_id":"1","category":"billing"}]}"#;
+        assert!(parse(categorized).unwrap().validate().is_ok());
+
+        let bad_pause = r#"{"version":1,"config":[],"rooms":[
+            {"room_id":1,"codename":"a","channel_id":"1","user_id":"1","pause":"nope"}]}"#;
+        assert!(parse(bad_pause).unwrap().validate().is_err());
+
+        let unknown_field = r#"{"version":1,"config":[],"rooms":[],"extra":true}"#;
+        assert!(parse(unknown_field).is_err());
+    }
+}
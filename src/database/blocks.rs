@@ -0,0 +1,150 @@
+use crate::error::Result;
+use serenity::model::id::{RoleId, UserId};
+use sqlx::SqlitePool;
+
+/// A user blocked from using the bot, kept regardless of whether they're still a member so the
+/// block role can be reapplied when they rejoin.
+pub struct Block;
+
+impl Block {
+    pub async fn add(pool: &SqlitePool, user_id: UserId) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        sqlx::query!("INSERT OR IGNORE INTO blocks (user_id) VALUES (?)", temp)
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// All blocked users, in the order they were blocked.
+    pub async fn all(pool: &SqlitePool) -> Result<Vec<UserId>> {
+        Ok(sqlx::query!("SELECT user_id FROM blocks ORDER BY rowid")
+            .fetch_all(pool)
+            .await
+            .map_err(anyhow::Error::from)?
+            .into_iter()
+            .map(|r| {
+                r.user_id
+                    .parse::<u64>()
+                    .expect("got malformed Block from database")
+                    .into()
+            })
+            .collect())
+    }
+
+    pub async fn exists(pool: &SqlitePool, user_id: UserId) -> Result<bool> {
+        // HACK: macro doesn't work, treats EXISTS() as a column name
+        let (exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM blocks WHERE user_id = ?)")
+                .bind(user_id.to_string())
+                .fetch_one(pool)
+                .await
+                .map_err(anyhow::Error::from)?;
+        Ok(exists)
+    }
+
+    /// Remembers roles taken from a blocked user, so they can be given back on unblock.
+    pub async fn add_stripped_roles(
+        pool: &SqlitePool,
+        user_id: UserId,
+        roles: &[RoleId],
+    ) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        for role in roles {
+            let role = &role.to_string();
+            sqlx::query!(
+                "INSERT OR IGNORE INTO block_stripped_roles (user_id, role_id) VALUES (?, ?)",
+                temp,
+                role
+            )
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        }
+        Ok(())
+    }
+
+    pub async fn stripped_roles(pool: &SqlitePool, user_id: UserId) -> Result<Vec<RoleId>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        Ok(sqlx::query!(
+            "SELECT role_id FROM block_stripped_roles WHERE user_id = ? ORDER BY rowid",
+            temp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|r| {
+            r.role_id
+                .parse::<u64>()
+                .expect("got malformed Block from database")
+                .into()
+        })
+        .collect())
+    }
+
+    /// Returns whether the user was blocked. Their stripped roles are forgotten as well.
+    pub async fn remove(pool: &SqlitePool, user_id: UserId) -> Result<bool> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        let res = sqlx::query!("DELETE FROM blocks WHERE user_id = ?", temp)
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::Block;
+
+    #[tokio::test]
+    async fn block_crud() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        Block::add(&pool, 1.into()).await.unwrap();
+        Block::add(&pool, 1.into()).await.unwrap();
+
+        // Get
+        assert!(Block::exists(&pool, 1.into()).await.unwrap());
+        assert!(!Block::exists(&pool, 2.into()).await.unwrap());
+
+        // List
+        Block::add(&pool, 3.into()).await.unwrap();
+        assert_eq!(Block::all(&pool).await.unwrap(), [1, 3]);
+
+        // Stripped roles
+        Block::add_stripped_roles(&pool, 1.into(), &[10.into(), 11.into()])
+            .await
+            .unwrap();
+        Block::add_stripped_roles(&pool, 1.into(), &[10.into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            Block::stripped_roles(&pool, 1.into()).await.unwrap(),
+            [10, 11]
+        );
+        assert!(Block::stripped_roles(&pool, 3.into())
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Delete
+        assert!(Block::remove(&pool, 1.into()).await.unwrap());
+        assert!(Block::stripped_roles(&pool, 1.into())
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!Block::remove(&pool, 1.into()).await.unwrap());
+        assert!(!Block::exists(&pool, 1.into()).await.unwrap());
+    }
+}
@@ -1,12 +1,27 @@
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug, Display},
+    result::Result as StdResult,
     str::FromStr,
 };
 
-use crate::error::Result;
+use crate::{
+    audit::Events, categories, error::Result, office_hours::Schedule, COALESCE_WINDOW_MAX_MS,
+    COALESCE_WINDOW_MIN_MS, EDIT_DEBOUNCE_MAX_MS, MAINTENANCE_INTERVAL_MAX, SLA_MINUTES_MAX,
+    THREAD_CREATE_RETRIES_MAX, TRANSCRIPT_LIMIT_MAX, TRANSCRIPT_RETENTION_MAX_DAYS,
+};
 use serenity::model::id::{ChannelId, RoleId};
 use sqlx::SqlitePool;
 
+/// Fails unless writing `key` affected exactly one row, as an upsert should.
+fn check_written(key: &str, rows: u64) -> Result<()> {
+    if rows != 1 {
+        tracing::error!(key, rows, "Unexpected row count while writing config.");
+        return Err(anyhow::anyhow!("writing config key `{}` affected {} rows", key, rows).into());
+    }
+    Ok(())
+}
+
 pub trait ConfigKey: Display {
     type Value: Display + FromStr;
 }
@@ -23,22 +38,31 @@ impl Config {
         T: ConfigKey,
         <<T as ConfigKey>::Value as FromStr>::Err: Debug,
     {
-        let key = &key.to_string();
-        Ok(sqlx::query!("SELECT value FROM config WHERE key = ?", key)
-            .fetch_optional(&self.0)
-            .await
-            .map_err(anyhow::Error::from)?
-            .map(|r| {
-                let value = &r.value;
-                T::Value::from_str(value).expect("got malformed config from database")
-            }))
+        Ok(self
+            .get_raw(&key.to_string())
+            .await?
+            .map(|value| T::Value::from_str(&value).expect("got malformed config from database")))
     }
 
     pub async fn set<T>(&self, key: T, value: T::Value) -> Result<()>
     where
         T: ConfigKey,
     {
-        let (key, value) = (&key.to_string(), &value.to_string());
+        self.set_raw(&key.to_string(), &value.to_string()).await
+    }
+
+    /// Gets the value of `key` as stored, without parsing it.
+    pub async fn get_raw(&self, key: &str) -> Result<Option<String>> {
+        Ok(sqlx::query!("SELECT value FROM config WHERE key = ?", key)
+            .fetch_optional(&self.0)
+            .await
+            .map_err(anyhow::Error::from)?
+            .map(|r| r.value))
+    }
+
+    /// Sets `key` to `value` as is. Callers must [`validate`] them first, since reading a
+    /// malformed value panics.
+    pub async fn set_raw(&self, key: &str, value: &str) -> Result<()> {
         let res = sqlx::query!(
             "INSERT INTO config (key, value) VALUES (?, ?)
             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
@@ -49,15 +73,17 @@ impl Config {
         .await
         .map_err(anyhow::Error::from)?;
 
-        assert_eq!(res.rows_affected(), 1u64);
-        Ok(())
+        check_written(key, res.rows_affected())
     }
 
     pub async fn unset<T>(&self, key: T) -> Result<()>
     where
         T: ConfigKey,
     {
-        let key = &key.to_string();
+        self.unset_raw(&key.to_string()).await
+    }
+
+    pub async fn unset_raw(&self, key: &str) -> Result<()> {
         sqlx::query!("DELETE FROM config WHERE key = ?", key)
             .execute(&self.0)
             .await
@@ -66,28 +92,221 @@ impl Config {
     }
 }
 
-pub struct Blockrole;
+/// A set of roles, stored as comma-separated IDs.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RoleIds(pub Vec<RoleId>);
+
+impl Display for RoleIds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids: Vec<_> = self.0.iter().map(|id| id.to_string()).collect();
+        write!(f, "{}", ids.join(","))
+    }
+}
+
+impl FromStr for RoleIds {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Self::default());
+        }
+
+        s.split(',')
+            .map(|id| id.parse::<u64>().map(RoleId))
+            .collect::<StdResult<_, _>>()
+            .map(Self)
+    }
+}
+
+/// A set of file extensions, stored comma-separated in lowercase and without dots.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Extensions(pub Vec<String>);
+
+impl Display for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(","))
+    }
+}
+
+impl FromStr for Extensions {
+    type Err = String;
 
-impl Display for Blockrole {
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        s.split(',')
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| match ext.contains(['.', '/', ' ']) {
+                true => Err(format!("invalid extension `{}`", ext)),
+                false => Ok(ext),
+            })
+            .collect::<StdResult<_, _>>()
+            .map(Self)
+    }
+}
+
+/// How users' messages are shown in their thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageStyle {
+    /// As the content of the bot's message.
+    #[default]
+    Plain,
+    /// In an embed showing the user's name and avatar.
+    Embed,
+}
+
+impl Display for MessageStyle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "blockrole")
+        match self {
+            MessageStyle::Plain => write!(f, "plain"),
+            MessageStyle::Embed => write!(f, "embed"),
+        }
     }
 }
 
-impl ConfigKey for Blockrole {
-    type Value = RoleId;
+impl FromStr for MessageStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        match s {
+            "plain" => Ok(MessageStyle::Plain),
+            "embed" => Ok(MessageStyle::Embed),
+            _ => Err(format!("unknown forward style `{}`", s)),
+        }
+    }
 }
 
-pub struct Inbox;
+/// Roles required to use commands, stored as comma-separated `command=role` pairs.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CommandRoles(pub BTreeMap<String, RoleId>);
 
-impl Display for Inbox {
+impl Display for CommandRoles {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "inbox")
+        let pairs: Vec<_> = self
+            .0
+            .iter()
+            .map(|(command, role)| format!("{}={}", command, role))
+            .collect();
+        write!(f, "{}", pairs.join(","))
     }
 }
 
-impl ConfigKey for Inbox {
-    type Value = ChannelId;
+impl FromStr for CommandRoles {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Self::default());
+        }
+
+        s.split(',')
+            .map(|pair| {
+                let (command, role) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected `command=role`, got `{}`", pair))?;
+                let role = role
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid role ID `{}`", role))?;
+                Ok((command.to_owned(), RoleId(role)))
+            })
+            .collect::<StdResult<_, _>>()
+            .map(Self)
+    }
+}
+
+/// The range numeric keys must be in, as enforced by their dedicated commands, so that they
+/// can't be bypassed with `/setconfig` or `/import`.
+fn bounds(key: &str) -> Option<(u64, u64)> {
+    match key {
+        "coalesce_window" => Some((COALESCE_WINDOW_MIN_MS, COALESCE_WINDOW_MAX_MS)),
+        "edit_debounce" => Some((0, EDIT_DEBOUNCE_MAX_MS)),
+        "maintenance_interval" => Some((1, MAINTENANCE_INTERVAL_MAX)),
+        "max_open_threads" => Some((1, i64::MAX as u64)),
+        "sla_minutes" => Some((1, SLA_MINUTES_MAX)),
+        "thread_create_retries" => Some((0, THREAD_CREATE_RETRIES_MAX)),
+        "transcript_limit" => Some((1, TRANSCRIPT_LIMIT_MAX as u64)),
+        "transcript_retention" => Some((1, TRANSCRIPT_RETENTION_MAX_DAYS)),
+        _ => None,
+    }
+}
+
+macro_rules! config_keys {
+    ($($name:ident => $key:literal: $value:ty,)*) => {
+        $(
+            pub struct $name;
+
+            impl Display for $name {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, $key)
+                }
+            }
+
+            impl ConfigKey for $name {
+                type Value = $value;
+            }
+        )*
+
+        /// All known config keys.
+        pub const KEYS: &[&str] = &[$($key),*];
+
+        /// Checks that `key` is a known config key and `value` is valid for it.
+        pub fn validate(key: &str, value: &str) -> StdResult<(), String> {
+            match key {
+                $($key => value
+                    .parse::<$value>()
+                    .map(|_| ())
+                    .map_err(|_| format!("invalid value for `{}`", key)),)*
+                _ => Err(format!("unknown config key `{}`", key)),
+            }?;
+
+            match (bounds(key), value.parse::<u64>()) {
+                (Some((min, max)), Ok(n)) if (min..=max).contains(&n) => Ok(()),
+                (Some((min, max)), _) => Err(format!(
+                    "`{}` must be between {} and {}",
+                    key, min, max
+                )),
+                (None, _) => Ok(()),
+            }
+        }
+    };
+}
+
+config_keys! {
+    AllowedExtensions => "allowed_extensions": Extensions,
+    Analytics => "analytics": bool,
+    AuditChannel => "audit_channel": ChannelId,
+    AuditEvents => "audit_events": Events,
+    BlockedExtensions => "blocked_extensions": Extensions,
+    Blockrole => "blockrole": RoleId,
+    BlockStripsRoles => "block_strips_roles": RoleIds,
+    Categories => "categories": categories::Categories,
+    IgnoreBots => "ignore_bots": bool,
+    Inbox => "inbox": ChannelId,
+    MaxOpenThreads => "max_open_threads": i64,
+    OverflowInbox => "overflow_inbox": ChannelId,
+    BlockEmoji => "block_emoji": String,
+    CloseDm => "close_dm": String,
+    CloseEmoji => "close_emoji": String,
+    CoalesceWindow => "coalesce_window": u64,
+    CommandPermissions => "command_permissions": CommandRoles,
+    Guild => "guild": u64,
+    CodenameCommand => "codename_command": String,
+    EditDebounce => "edit_debounce": u64,
+    ForwardStyle => "forward_style": MessageStyle,
+    MaintenanceInterval => "maintenance_interval": u64,
+    MaintenanceMessage => "maintenance_message": String,
+    MaintenanceMode => "maintenance_mode": bool,
+    OfficeHours => "office_hours": Schedule,
+    OutOfHoursMessage => "out_of_hours_message": String,
+    OutOfHoursHold => "out_of_hours_hold": bool,
+    ReopenSummary => "reopen_summary": bool,
+    ReplyPingRole => "reply_ping_role": RoleId,
+    RevealUsername => "reveal_username": bool,
+    ShowFooter => "show_footer": bool,
+    ShowThreadCount => "show_thread_count": bool,
+    SlaMinutes => "sla_minutes": u64,
+    ThreadCreateRetries => "thread_create_retries": u64,
+    TranscriptLimit => "transcript_limit": usize,
+    TranscriptRetention => "transcript_retention": u64,
 }
 
 #[cfg(test)]
@@ -95,7 +314,9 @@ mod tests {
     use serenity::model::id::{ChannelId, RoleId};
     use sqlx::SqlitePool;
 
-    use super::{Blockrole, Config, Inbox};
+    use super::{
+        check_written, validate, Blockrole, CommandRoles, Config, Extensions, Inbox, RoleIds,
+    };
 
     #[tokio::test]
     async fn config_crud() {
@@ -126,6 +347,14 @@ mod tests {
         assert_eq!(blockrole, RoleId(321));
         assert_eq!(inbox, ChannelId(654));
 
+        // Raw
+        config.set_raw("inbox", "789").await.unwrap();
+        assert_eq!(config.get(Inbox).await.unwrap(), Some(ChannelId(789)));
+        assert_eq!(
+            config.get_raw("blockrole").await.unwrap(),
+            Some("321".to_owned())
+        );
+
         // Delete
         config.unset(Blockrole).await.unwrap();
         config.unset(Inbox).await.unwrap();
@@ -136,4 +365,71 @@ mod tests {
         assert_eq!(blockrole, None);
         assert_eq!(inbox, None);
     }
+
+    #[test]
+    fn config_write_count() {
+        assert!(check_written("inbox", 1).is_ok());
+        assert!(check_written("inbox", 0).is_err());
+        assert!(check_written("inbox", 2).is_err());
+    }
+
+    #[test]
+    fn config_validate() {
+        assert!(validate("blockrole", "123").is_ok());
+        assert!(validate("blockrole", "not an id").is_err());
+        assert!(validate("out_of_hours_hold", "true").is_ok());
+        assert!(validate("office_hours", "09:00-17:00 UTC").is_ok());
+        assert!(validate("office_hours", "whenever").is_err());
+        assert!(validate("block_strips_roles", "1,2").is_ok());
+        assert!(validate("block_strips_roles", "1,,2").is_err());
+        assert!(validate("command_permissions", "close=1,block=2").is_ok());
+        assert!(validate("command_permissions", "close").is_err());
+        assert!(validate("blocked_extensions", ".exe, bat").is_ok());
+        assert!(validate("blocked_extensions", "tar.gz").is_err());
+        assert!(validate("forward_style", "embed").is_ok());
+        assert!(validate("forward_style", "fancy").is_err());
+        assert!(validate("audit_events", "open,close").is_ok());
+        assert!(validate("audit_events", "everything").is_err());
+        assert!(validate("categories", "billing,technical").is_ok());
+        assert!(validate("categories", "a:b").is_err());
+        assert!(validate("no_such_key", "123").is_err());
+
+        assert!(validate("coalesce_window", "500").is_ok());
+        assert!(validate("coalesce_window", "0").is_err());
+        assert!(validate("maintenance_interval", "0").is_err());
+        assert!(validate("max_open_threads", "-1").is_err());
+        assert!(validate("sla_minutes", "18446744073709551615").is_err());
+        assert!(validate("transcript_limit", "10001").is_err());
+    }
+
+    #[test]
+    fn role_ids_roundtrip() {
+        let roles = RoleIds(vec![RoleId(1), RoleId(23)]);
+        assert_eq!(roles.to_string(), "1,23");
+        assert_eq!("1,23".parse::<RoleIds>().unwrap(), roles);
+        assert_eq!("".parse::<RoleIds>().unwrap(), RoleIds::default());
+    }
+
+    #[test]
+    fn extensions_roundtrip() {
+        let extensions: Extensions = ".EXE, bat,".parse().unwrap();
+        assert_eq!(extensions, Extensions(vec!["exe".into(), "bat".into()]));
+        assert_eq!(extensions.to_string(), "exe,bat");
+    }
+
+    #[test]
+    fn command_roles_roundtrip() {
+        let roles = CommandRoles(
+            [
+                ("close".to_owned(), RoleId(1)),
+                ("block".to_owned(), RoleId(2)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(roles.to_string(), "block=2,close=1");
+        assert_eq!("block=2,close=1".parse::<CommandRoles>().unwrap(), roles);
+        assert_eq!("".parse::<CommandRoles>().unwrap(), CommandRoles::default());
+        assert!("close=abc".parse::<CommandRoles>().is_err());
+    }
 }
@@ -0,0 +1,296 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::error::Result;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+/// Which side of a room a message failed to reach.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    ToThread,
+    ToUser,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::ToThread => write!(f, "thread"),
+            Direction::ToUser => write!(f, "user"),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "thread" => Ok(Direction::ToThread),
+            "user" => Ok(Direction::ToUser),
+            _ => Err(format!("unknown direction `{}`", s)),
+        }
+    }
+}
+
+/// A forwarded message that could not be delivered.
+pub struct DeadLetter {
+    pub dead_letter_id: i64,
+    pub direction: Direction,
+    pub target_id: u64,
+    pub content: String,
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RawDeadLetter> for DeadLetter {
+    fn from(value: RawDeadLetter) -> Self {
+        Self {
+            dead_letter_id: value.dead_letter_id,
+            direction: value
+                .direction
+                .parse()
+                .expect("got malformed DeadLetter from database"),
+            target_id: value
+                .target_id
+                .parse()
+                .expect("got malformed DeadLetter from database"),
+            content: value.content,
+            error: value.error,
+            created_at: Utc.timestamp_opt(value.created_at, 0).unwrap(),
+        }
+    }
+}
+
+impl DeadLetter {
+    pub async fn new(
+        pool: &SqlitePool,
+        room_id: Option<i64>,
+        direction: Direction,
+        target_id: u64,
+        content: String,
+        error: String,
+    ) -> Result<Self> {
+        let created_at = Utc::now();
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let (direction_str, target_str, timestamp) = (
+            &direction.to_string(),
+            &target_id.to_string(),
+            created_at.timestamp(),
+        );
+        let dead_letter_id = sqlx::query!(
+            "INSERT INTO dead_letters (room_id, direction, target_id, content, error, created_at)
+            VALUES (?, ?, ?, ?, ?, ?) RETURNING dead_letter_id",
+            room_id,
+            direction_str,
+            target_str,
+            content,
+            error,
+            timestamp
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .dead_letter_id;
+
+        Ok(Self {
+            dead_letter_id,
+            direction,
+            target_id,
+            content,
+            error,
+            created_at,
+        })
+    }
+
+    /// The most recent dead letters, newest first.
+    pub async fn recent(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            RawDeadLetter,
+            "SELECT dead_letter_id, direction, target_id, content, error, created_at
+            FROM dead_letters ORDER BY created_at DESC, dead_letter_id DESC LIMIT ?",
+            limit
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(DeadLetter::from)
+        .collect())
+    }
+
+    /// The room's most recent message that failed to be delivered in `direction`.
+    pub async fn latest(
+        pool: &SqlitePool,
+        room_id: i64,
+        direction: Direction,
+    ) -> Result<Option<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let direction_str = &direction.to_string();
+        Ok(sqlx::query_as!(
+            RawDeadLetter,
+            "SELECT dead_letter_id, direction, target_id, content, error, created_at
+            FROM dead_letters WHERE room_id = ? AND direction = ?
+            ORDER BY created_at DESC, dead_letter_id DESC LIMIT 1",
+            room_id,
+            direction_str
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(DeadLetter::from))
+    }
+
+    pub async fn delete(self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM dead_letters WHERE dead_letter_id = ?",
+            self.dead_letter_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// How many messages for a room failed to be delivered in `direction` since `since`.
+    pub async fn count_since(
+        pool: &SqlitePool,
+        room_id: i64,
+        direction: Direction,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let (direction_str, timestamp) = (&direction.to_string(), since.timestamp());
+        Ok(sqlx::query!(
+            "SELECT COUNT(*) AS count FROM dead_letters
+            WHERE room_id = ? AND direction = ? AND created_at >= ?",
+            room_id,
+            direction_str,
+            timestamp
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .count
+        .into())
+    }
+
+    /// Deletes dead letters older than `retention`, returning how many were removed.
+    pub async fn prune(pool: &SqlitePool, retention: Duration) -> Result<u64> {
+        let cutoff = (Utc::now() - retention).timestamp();
+        let res = sqlx::query!("DELETE FROM dead_letters WHERE created_at < ?", cutoff)
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[derive(FromRow)]
+struct RawDeadLetter {
+    dead_letter_id: i64,
+    direction: String,
+    target_id: String,
+    content: String,
+    error: String,
+    created_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use sqlx::SqlitePool;
+
+    use super::{DeadLetter, Direction};
+    use crate::database::rooms::Room;
+
+    #[tokio::test]
+    async fn dead_letters() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        for i in 0..3 {
+            DeadLetter::new(
+                &pool,
+                None,
+                Direction::ToUser,
+                42,
+                format!("message {}", i),
+                "Cannot send messages to this user".into(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // Get
+        let recent = DeadLetter::recent(&pool, 2).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].content, "message 2");
+        assert_eq!(recent[0].direction, Direction::ToUser);
+        assert_eq!(recent[0].target_id, 42);
+
+        // Per room
+        let room = Room::new(&pool, "archived".into(), 1.into(), 42.into())
+            .await
+            .unwrap();
+        let since = Utc::now() - Duration::minutes(1);
+        DeadLetter::new(
+            &pool,
+            Some(room.room_id),
+            Direction::ToThread,
+            1,
+            "hello?".into(),
+            "Thread is locked".into(),
+        )
+        .await
+        .unwrap();
+        let latest = DeadLetter::latest(&pool, room.room_id, Direction::ToThread)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.content, "hello?");
+        assert!(DeadLetter::latest(&pool, room.room_id, Direction::ToUser)
+            .await
+            .unwrap()
+            .is_none());
+
+        let count = |direction| DeadLetter::count_since(&pool, room.room_id, direction, since);
+        assert_eq!(count(Direction::ToThread).await.unwrap(), 1);
+        assert_eq!(count(Direction::ToUser).await.unwrap(), 0);
+        assert_eq!(
+            DeadLetter::count_since(
+                &pool,
+                room.room_id,
+                Direction::ToThread,
+                Utc::now() + Duration::minutes(1)
+            )
+            .await
+            .unwrap(),
+            0
+        );
+
+        // Delete
+        latest.delete(&pool).await.unwrap();
+        assert!(DeadLetter::latest(&pool, room.room_id, Direction::ToThread)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Prune
+        assert_eq!(
+            DeadLetter::prune(&pool, Duration::days(1)).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            DeadLetter::prune(&pool, Duration::seconds(-1))
+                .await
+                .unwrap(),
+            3
+        );
+        assert!(DeadLetter::recent(&pool, 10).await.unwrap().is_empty());
+    }
+}
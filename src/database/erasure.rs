@@ -0,0 +1,178 @@
+use crate::error::Result;
+use serenity::model::id::{ChannelId, UserId};
+use sqlx::SqlitePool;
+
+/// What was deleted when forgetting a user.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Erasure {
+    /// Threads of the user's deleted rooms, which are left for the caller to clean up.
+    pub threads: Vec<ChannelId>,
+    pub dead_letters: u64,
+    pub reminders: u64,
+    pub transcripts: u64,
+    pub blocks: u64,
+    pub aliases: u64,
+}
+
+impl Erasure {
+    /// Deletes everything stored about a user. Running this again for the same user does
+    /// nothing.
+    pub async fn forget(pool: &SqlitePool, user_id: UserId) -> Result<Self> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        let mut tx = pool.begin().await.map_err(anyhow::Error::from)?;
+
+        // unlike message links and reminders, dead letters outlive their room
+        let dead_letters = sqlx::query!(
+            "DELETE FROM dead_letters
+            WHERE room_id IN (SELECT room_id FROM rooms WHERE user_id = ?)
+            OR (direction = 'user' AND target_id = ?)",
+            temp,
+            temp
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(anyhow::Error::from)?
+        .rows_affected();
+
+        let reminders = sqlx::query!(
+            "DELETE FROM reminders
+            WHERE room_id IN (SELECT room_id FROM rooms WHERE user_id = ?) OR user_id = ?",
+            temp,
+            temp
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(anyhow::Error::from)?
+        .rows_affected();
+
+        let threads = sqlx::query!(
+            "DELETE FROM rooms WHERE user_id = ? RETURNING channel_id",
+            temp
+        )
+        .fetch_all(&mut tx)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|r| {
+            r.channel_id
+                .parse::<u64>()
+                .expect("got malformed Room object from database")
+                .into()
+        })
+        .collect();
+
+        let transcripts = sqlx::query!("DELETE FROM transcripts WHERE user_id = ?", temp)
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?
+            .rows_affected();
+
+        let blocks = sqlx::query!("DELETE FROM blocks WHERE user_id = ?", temp)
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?
+            .rows_affected();
+
+        let aliases = sqlx::query!("DELETE FROM aliases WHERE user_id = ?", temp)
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?
+            .rows_affected();
+
+        tx.commit().await.map_err(anyhow::Error::from)?;
+        Ok(Self {
+            threads,
+            dead_letters,
+            reminders,
+            transcripts,
+            blocks,
+            aliases,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sqlx::SqlitePool;
+
+    use super::Erasure;
+    use crate::database::{
+        aliases::Alias,
+        blocks::Block,
+        dead_letters::{DeadLetter, Direction},
+        messages::MessageLink,
+        reminders::Reminder,
+        rooms::Room,
+        transcripts::Transcript,
+    };
+
+    #[tokio::test]
+    async fn forget_user() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        let room = Room::new(&pool, "quiet owl".into(), 1.into(), 2.into())
+            .await
+            .unwrap();
+        let other = Room::new(&pool, "loud owl".into(), 3.into(), 4.into())
+            .await
+            .unwrap();
+        MessageLink::new(&pool, 10.into(), 11.into(), room.room_id)
+            .await
+            .unwrap();
+        Reminder::new(&pool, &room, 5.into(), Utc::now())
+            .await
+            .unwrap();
+        Reminder::new(&pool, &other, 5.into(), Utc::now())
+            .await
+            .unwrap();
+        DeadLetter::new(
+            &pool,
+            Some(room.room_id),
+            Direction::ToUser,
+            2,
+            "hi".into(),
+            "".into(),
+        )
+        .await
+        .unwrap();
+        DeadLetter::new(&pool, None, Direction::ToUser, 2, "hi".into(), "".into())
+            .await
+            .unwrap();
+        Transcript::new(&pool, "old owl", 2.into(), "hi")
+            .await
+            .unwrap();
+        Block::add(&pool, 2.into()).await.unwrap();
+        Alias::set(&pool, 2.into(), "quiet owl").await.unwrap();
+
+        // Delete
+        let erasure = Erasure::forget(&pool, 2.into()).await.unwrap();
+        assert_eq!(
+            erasure,
+            Erasure {
+                threads: vec![1.into()],
+                dead_letters: 2,
+                reminders: 1,
+                transcripts: 1,
+                blocks: 1,
+                aliases: 1,
+            }
+        );
+        assert!(Room::get_by_user(&pool, 2.into()).await.unwrap().is_none());
+        assert!(MessageLink::get_by_dm_message(&pool, 11.into())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!Block::exists(&pool, 2.into()).await.unwrap());
+        assert_eq!(Alias::get(&pool, 2.into()).await.unwrap(), None);
+        assert!(Room::get_by_user(&pool, 4.into()).await.unwrap().is_some());
+
+        // forgetting again is harmless
+        let erasure = Erasure::forget(&pool, 2.into()).await.unwrap();
+        assert_eq!(erasure, Erasure::default());
+    }
+}
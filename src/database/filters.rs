@@ -0,0 +1,81 @@
+use crate::error::Result;
+use sqlx::SqlitePool;
+
+/// A pattern redacted from messages users send to the bot.
+pub struct Filter {
+    pub filter_id: i64,
+    pub pattern: String,
+}
+
+impl Filter {
+    pub async fn new(pool: &SqlitePool, pattern: String) -> Result<Self> {
+        let filter_id = sqlx::query!(
+            "INSERT INTO filters (pattern) VALUES (?) RETURNING filter_id",
+            pattern
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .filter_id;
+
+        Ok(Self { filter_id, pattern })
+    }
+
+    pub async fn all(pool: &SqlitePool) -> Result<Vec<Self>> {
+        Ok(
+            sqlx::query_as!(Filter, "SELECT * FROM filters ORDER BY filter_id")
+                .fetch_all(pool)
+                .await
+                .map_err(anyhow::Error::from)?,
+        )
+    }
+
+    pub async fn exists(pool: &SqlitePool, pattern: &str) -> Result<bool> {
+        // HACK: macro doesn't work, treats EXISTS() as a column name
+        let (exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM filters WHERE pattern = ?)")
+                .bind(pattern)
+                .fetch_one(pool)
+                .await
+                .map_err(anyhow::Error::from)?;
+        Ok(exists)
+    }
+
+    /// Returns whether a filter with the given ID existed.
+    pub async fn delete(pool: &SqlitePool, filter_id: i64) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM filters WHERE filter_id = ?", filter_id)
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::Filter;
+
+    #[tokio::test]
+    async fn filter_crud() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        let invites = Filter::new(&pool, r"discord\.gg/\w+".into()).await.unwrap();
+        Filter::new(&pool, "badword".into()).await.unwrap();
+        assert!(Filter::exists(&pool, "badword").await.unwrap());
+
+        // Get
+        let filters = Filter::all(&pool).await.unwrap();
+        let patterns: Vec<_> = filters.iter().map(|f| f.pattern.as_str()).collect();
+        assert_eq!(patterns, [r"discord\.gg/\w+", "badword"]);
+
+        // Delete
+        assert!(Filter::delete(&pool, invites.filter_id).await.unwrap());
+        assert!(!Filter::delete(&pool, invites.filter_id).await.unwrap());
+        assert_eq!(Filter::all(&pool).await.unwrap().len(), 1);
+    }
+}
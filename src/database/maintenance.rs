@@ -0,0 +1,107 @@
+use crate::error::Result;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+
+use super::dead_letters::DeadLetter;
+
+/// What a maintenance run cleaned up.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Maintenance {
+    pub dead_letters: u64,
+    pub transcripts: u64,
+    /// How many bytes `VACUUM` freed up in the database file.
+    pub reclaimed: i64,
+}
+
+impl Maintenance {
+    /// Deletes dead letters older than `dead_letter_retention` and, if given, transcripts older
+    /// than `transcript_retention`, then compacts the database.
+    pub async fn run(
+        pool: &SqlitePool,
+        dead_letter_retention: Duration,
+        transcript_retention: Option<Duration>,
+    ) -> Result<Self> {
+        let before = size(pool).await?;
+
+        let dead_letters = DeadLetter::prune(pool, dead_letter_retention).await?;
+        let transcripts = match transcript_retention {
+            Some(retention) => {
+                let cutoff = (Utc::now() - retention).timestamp();
+                sqlx::query!("DELETE FROM transcripts WHERE closed_at < ?", cutoff)
+                    .execute(pool)
+                    .await
+                    .map_err(anyhow::Error::from)?
+                    .rows_affected()
+            }
+            None => 0,
+        };
+
+        sqlx::query!("VACUUM")
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        Ok(Self {
+            dead_letters,
+            transcripts,
+            reclaimed: before - size(pool).await?,
+        })
+    }
+}
+
+/// The size of the database in bytes, not counting the write-ahead log.
+async fn size(pool: &SqlitePool) -> Result<i64> {
+    Ok(sqlx::query!(
+        r#"SELECT page_count * page_size AS "size!: i64"
+        FROM pragma_page_count(), pragma_page_size()"#
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(anyhow::Error::from)?
+    .size)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sqlx::SqlitePool;
+
+    use super::Maintenance;
+    use crate::database::{
+        dead_letters::{DeadLetter, Direction},
+        transcripts::Transcript,
+    };
+
+    #[tokio::test]
+    async fn maintenance() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        DeadLetter::new(
+            &pool,
+            None,
+            Direction::ToUser,
+            42,
+            "hello".into(),
+            "Cannot send messages to this user".into(),
+        )
+        .await
+        .unwrap();
+        Transcript::new(&pool, "loud owl", 42.into(), "hello")
+            .await
+            .unwrap();
+
+        // Delete, with retentions ending in the future so everything is old enough
+        let kept = Maintenance::run(&pool, Duration::days(1), None)
+            .await
+            .unwrap();
+        assert_eq!((kept.dead_letters, kept.transcripts), (0, 0));
+
+        let purged = Maintenance::run(&pool, Duration::days(-1), Some(Duration::days(-1)))
+            .await
+            .unwrap();
+        assert_eq!((purged.dead_letters, purged.transcripts), (1, 1));
+    }
+}
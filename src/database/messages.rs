@@ -0,0 +1,135 @@
+use crate::error::Result;
+use serenity::model::id::MessageId;
+use sqlx::{FromRow, SqlitePool};
+use std::{num::ParseIntError, result::Result as StdResult};
+
+/// Pairs a message in a room's thread with its counterpart in the user's DMs.
+pub struct MessageLink {
+    pub thread_message_id: MessageId,
+    pub dm_message_id: MessageId,
+    pub room_id: i64,
+}
+
+impl TryFrom<RawMessageLink> for MessageLink {
+    type Error = ParseIntError;
+
+    fn try_from(value: RawMessageLink) -> StdResult<Self, Self::Error> {
+        Ok(Self {
+            thread_message_id: value.thread_message_id.parse::<u64>()?.into(),
+            dm_message_id: value.dm_message_id.parse::<u64>()?.into(),
+            room_id: value.room_id,
+        })
+    }
+}
+
+impl MessageLink {
+    pub async fn new(
+        pool: &SqlitePool,
+        thread_message_id: MessageId,
+        dm_message_id: MessageId,
+        room_id: i64,
+    ) -> Result<Self> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let (thread_str, dm_str) = (&thread_message_id.to_string(), &dm_message_id.to_string());
+        sqlx::query!(
+            "INSERT INTO messages (thread_message_id, dm_message_id, room_id) VALUES (?, ?, ?)",
+            thread_str,
+            dm_str,
+            room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        Ok(Self {
+            thread_message_id,
+            dm_message_id,
+            room_id,
+        })
+    }
+
+    pub async fn get_by_thread_message(
+        pool: &SqlitePool,
+        thread_message_id: MessageId,
+    ) -> Result<Option<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &thread_message_id.to_string();
+        Ok(sqlx::query_as!(
+            RawMessageLink,
+            "SELECT thread_message_id, dm_message_id, room_id FROM messages WHERE thread_message_id = ?",
+            temp
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(|rl| MessageLink::try_from(rl).expect("got malformed MessageLink from database")))
+    }
+
+    pub async fn get_by_dm_message(
+        pool: &SqlitePool,
+        dm_message_id: MessageId,
+    ) -> Result<Option<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &dm_message_id.to_string();
+        Ok(sqlx::query_as!(
+            RawMessageLink,
+            "SELECT thread_message_id, dm_message_id, room_id FROM messages WHERE dm_message_id = ?",
+            temp
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(|rl| MessageLink::try_from(rl).expect("got malformed MessageLink from database")))
+    }
+}
+
+#[derive(FromRow)]
+struct RawMessageLink {
+    thread_message_id: String,
+    dm_message_id: String,
+    room_id: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::id::{ChannelId, MessageId, UserId};
+    use sqlx::SqlitePool;
+
+    use super::MessageLink;
+    use crate::database::rooms::Room;
+
+    #[tokio::test]
+    async fn message_links() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let room = Room::new(&pool, "peaceful bonefish".into(), ChannelId(1), UserId(2))
+            .await
+            .unwrap();
+
+        // Create
+        MessageLink::new(&pool, MessageId(10), MessageId(20), room.room_id)
+            .await
+            .unwrap();
+
+        // Get
+        let link = MessageLink::get_by_thread_message(&pool, MessageId(10))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.dm_message_id, MessageId(20));
+        let link = MessageLink::get_by_dm_message(&pool, MessageId(20))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.thread_message_id, MessageId(10));
+        assert_eq!(link.room_id, room.room_id);
+
+        // Deleting the room removes its links
+        room.delete(&pool).await.unwrap();
+        let link = MessageLink::get_by_thread_message(&pool, MessageId(10))
+            .await
+            .unwrap();
+        assert!(link.is_none());
+    }
+}
@@ -1,2 +1,17 @@
+pub mod aliases;
+pub mod backup;
+pub mod blocks;
 pub mod config;
+pub mod dead_letters;
+pub mod erasure;
+pub mod filters;
+pub mod maintenance;
+pub mod messages;
+pub mod notification_optouts;
+pub mod queued_messages;
+pub mod reminders;
 pub mod rooms;
+pub mod scheduled_messages;
+pub mod snippets;
+pub mod stats;
+pub mod transcripts;
@@ -0,0 +1,73 @@
+use crate::error::Result;
+use serenity::model::id::UserId;
+use sqlx::SqlitePool;
+
+/// A staff member who doesn't want to be pinged about users' replies.
+pub struct NotificationOptout;
+
+impl NotificationOptout {
+    /// Mutes the staff member if they weren't already, or unmutes them otherwise. Returns
+    /// whether they're muted now.
+    pub async fn toggle(pool: &SqlitePool, user_id: UserId) -> Result<bool> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        let res = sqlx::query!("DELETE FROM notification_optouts WHERE user_id = ?", temp)
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        if res.rows_affected() > 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "INSERT INTO notification_optouts (user_id) VALUES (?)",
+            temp
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(true)
+    }
+
+    pub async fn all(pool: &SqlitePool) -> Result<Vec<UserId>> {
+        Ok(
+            sqlx::query!("SELECT user_id FROM notification_optouts ORDER BY rowid")
+                .fetch_all(pool)
+                .await
+                .map_err(anyhow::Error::from)?
+                .into_iter()
+                .map(|r| {
+                    r.user_id
+                        .parse::<u64>()
+                        .expect("got malformed NotificationOptout from database")
+                        .into()
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::NotificationOptout;
+
+    #[tokio::test]
+    async fn optout_toggle() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        assert!(NotificationOptout::toggle(&pool, 1.into()).await.unwrap());
+        assert!(NotificationOptout::toggle(&pool, 2.into()).await.unwrap());
+
+        // Get
+        assert_eq!(NotificationOptout::all(&pool).await.unwrap(), [1, 2]);
+
+        // Delete
+        assert!(!NotificationOptout::toggle(&pool, 1.into()).await.unwrap());
+        assert_eq!(NotificationOptout::all(&pool).await.unwrap(), [2]);
+    }
+}
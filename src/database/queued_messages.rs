@@ -0,0 +1,79 @@
+use crate::error::Result;
+use serenity::model::id::MessageId;
+use sqlx::SqlitePool;
+
+/// A staff message held back while its room is paused, to be sent on `/resume`.
+pub struct QueuedMessage;
+
+impl QueuedMessage {
+    pub async fn add(pool: &SqlitePool, room_id: i64, thread_message_id: MessageId) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &thread_message_id.to_string();
+        sqlx::query!(
+            "INSERT OR IGNORE INTO queued_messages (thread_message_id, room_id) VALUES (?, ?)",
+            temp,
+            room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Removes and returns the room's queued messages, oldest first.
+    pub async fn take_all(pool: &SqlitePool, room_id: i64) -> Result<Vec<MessageId>> {
+        let mut queued: Vec<MessageId> = sqlx::query!(
+            "DELETE FROM queued_messages WHERE room_id = ? RETURNING thread_message_id",
+            room_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|r| {
+            r.thread_message_id
+                .parse::<u64>()
+                .expect("got malformed QueuedMessage from database")
+                .into()
+        })
+        .collect();
+
+        // message IDs increase over time
+        queued.sort();
+        Ok(queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::QueuedMessage;
+    use crate::database::rooms::Room;
+
+    #[tokio::test]
+    async fn queue_messages() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let room = Room::new(&pool, "paused owl".into(), 1.into(), 2.into())
+            .await
+            .unwrap();
+
+        // Create
+        QueuedMessage::add(&pool, room.room_id, 12.into())
+            .await
+            .unwrap();
+        QueuedMessage::add(&pool, room.room_id, 11.into())
+            .await
+            .unwrap();
+
+        // Take
+        let queued = QueuedMessage::take_all(&pool, room.room_id).await.unwrap();
+        assert_eq!(queued, [11, 12]);
+        assert!(QueuedMessage::take_all(&pool, room.room_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serenity::model::id::{ChannelId, UserId};
+use sqlx::{FromRow, SqlitePool};
+use std::{num::ParseIntError, result::Result as StdResult};
+
+use super::rooms::Room;
+
+/// A request to be pinged in a room's thread at a later time.
+pub struct Reminder {
+    pub reminder_id: i64,
+    pub channel_id: ChannelId,
+    /// Who asked to be reminded.
+    pub user_id: UserId,
+}
+
+impl TryFrom<RawReminder> for Reminder {
+    type Error = ParseIntError;
+
+    fn try_from(value: RawReminder) -> StdResult<Self, Self::Error> {
+        Ok(Self {
+            reminder_id: value.reminder_id,
+            channel_id: value.channel_id.parse::<u64>()?.into(),
+            user_id: value.user_id.parse::<u64>()?.into(),
+        })
+    }
+}
+
+impl Reminder {
+    pub async fn new(
+        pool: &SqlitePool,
+        room: &Room,
+        user_id: UserId,
+        due_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let (channel_str, user_str, timestamp) = (
+            &room.channel_id.to_string(),
+            &user_id.to_string(),
+            due_at.timestamp(),
+        );
+        let reminder_id = sqlx::query!(
+            "INSERT INTO reminders (room_id, channel_id, user_id, due_at) VALUES (?, ?, ?, ?)
+            RETURNING reminder_id",
+            room.room_id,
+            channel_str,
+            user_str,
+            timestamp
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .reminder_id;
+
+        Ok(Self {
+            reminder_id,
+            channel_id: room.channel_id,
+            user_id,
+        })
+    }
+
+    /// Reminders due at or before `now`, oldest first.
+    pub async fn due(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<Self>> {
+        let timestamp = now.timestamp();
+        Ok(sqlx::query_as!(
+            RawReminder,
+            "SELECT reminder_id, channel_id, user_id FROM reminders
+            WHERE due_at <= ? ORDER BY due_at",
+            timestamp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rr| Reminder::try_from(rr).expect("got malformed Reminder from database"))
+        .collect())
+    }
+
+    /// Moves the reminders of `from` over to `to`, e.g. before `from` is merged into it.
+    /// Returns how many reminders were moved.
+    pub async fn move_all(pool: &SqlitePool, from: &Room, to: &Room) -> Result<u64> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let channel_str = &to.channel_id.to_string();
+        let res = sqlx::query!(
+            "UPDATE reminders SET room_id = ?, channel_id = ? WHERE room_id = ?",
+            to.room_id,
+            channel_str,
+            from.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn delete(self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM reminders WHERE reminder_id = ?",
+            self.reminder_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
+
+#[derive(FromRow)]
+struct RawReminder {
+    reminder_id: i64,
+    channel_id: String,
+    user_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use sqlx::SqlitePool;
+
+    use super::{Reminder, Room};
+
+    #[tokio::test]
+    async fn reminder_crud() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let room = Room::new(&pool, "quiet owl".into(), 1.into(), 2.into())
+            .await
+            .unwrap();
+
+        // Create
+        let now = Utc::now();
+        Reminder::new(&pool, &room, 3.into(), now - Duration::minutes(1))
+            .await
+            .unwrap();
+        Reminder::new(&pool, &room, 3.into(), now + Duration::hours(1))
+            .await
+            .unwrap();
+
+        // Get
+        let due = Reminder::due(&pool, now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].channel_id, 1);
+        assert_eq!(due[0].user_id, 3);
+
+        // Delete
+        for reminder in due {
+            reminder.delete(&pool).await.unwrap();
+        }
+        assert!(Reminder::due(&pool, now).await.unwrap().is_empty());
+
+        // Move
+        let other = Room::with_category(
+            &pool,
+            "loud crow".into(),
+            4.into(),
+            2.into(),
+            Some("billing".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(Reminder::move_all(&pool, &room, &other).await.unwrap(), 1);
+        room.delete(&pool).await.unwrap();
+        let later = now + Duration::days(1);
+        let due = Reminder::due(&pool, later).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].channel_id, 4);
+
+        // closing the room drops its reminders
+        other.delete(&pool).await.unwrap();
+        assert!(Reminder::due(&pool, later).await.unwrap().is_empty());
+    }
+}
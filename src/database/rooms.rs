@@ -1,13 +1,71 @@
 use crate::error::Result;
+use chrono::{DateTime, TimeZone, Utc};
 use serenity::model::id::{ChannelId, UserId};
 use sqlx::{FromRow, SqlitePool};
-use std::{num::ParseIntError, result::Result as StdResult};
+use std::{
+    fmt::{self, Display},
+    num::ParseIntError,
+    result::Result as StdResult,
+    str::FromStr,
+};
+
+/// What happens to staff messages while forwarding to the user is paused with `/pause`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pause {
+    /// Staff messages are sent on `/resume`.
+    Queue,
+    /// Staff messages are never sent.
+    Drop,
+}
+
+impl Display for Pause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pause::Queue => write!(f, "queue"),
+            Pause::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+impl FromStr for Pause {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        match s {
+            "queue" => Ok(Pause::Queue),
+            "drop" => Ok(Pause::Drop),
+            _ => Err(format!("unknown pause `{}`", s)),
+        }
+    }
+}
 
 pub struct Room {
     pub room_id: i64,
     pub codename: String,
     pub channel_id: ChannelId,
     pub user_id: UserId,
+    /// When a message was last forwarded in either direction.
+    pub last_activity_at: DateTime<Utc>,
+    /// Whether the room's thread was archived, e.g. by Discord after a period of inactivity.
+    pub archived: bool,
+    /// Set with `/priority`, where higher is more urgent.
+    pub priority: i64,
+    /// The staff member handling the room, set with `/claim`.
+    pub claimed_by: Option<UserId>,
+    /// The channel the room's thread was opened in, if known.
+    pub inbox_id: Option<ChannelId>,
+    /// Set while forwarding to the user is paused with `/pause`.
+    pub pause: Option<Pause>,
+    /// Set with `/snooze`, hiding the room from `/list` until then.
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// When the room was opened, unknown for rooms from before this was recorded.
+    pub opened_at: Option<DateTime<Utc>>,
+    /// When staff first replied to the user, for `/report` and `sla_minutes` alerts.
+    pub first_staff_reply_at: Option<DateTime<Utc>>,
+    /// Whether staff were already alerted that the room went unanswered past `sla_minutes`.
+    pub sla_alerted: bool,
+    /// What the user picked the room to be about, while `categories` are set up.
+    pub category: Option<String>,
 }
 
 impl TryFrom<RawRoom> for Room {
@@ -19,6 +77,33 @@ impl TryFrom<RawRoom> for Room {
             codename: value.codename,
             channel_id: value.channel_id.parse::<u64>()?.into(),
             user_id: value.user_id.parse::<u64>()?.into(),
+            last_activity_at: Utc.timestamp_opt(value.last_activity_at, 0).unwrap(),
+            archived: value.archived,
+            priority: value.priority,
+            claimed_by: match value.claimed_by {
+                Some(id) => Some(id.parse::<u64>()?.into()),
+                None => None,
+            },
+            inbox_id: match value.inbox_id {
+                Some(id) => Some(id.parse::<u64>()?.into()),
+                None => None,
+            },
+            pause: value.pause.map(|pause| {
+                pause
+                    .parse()
+                    .expect("got malformed Room object from database")
+            }),
+            snoozed_until: value
+                .snoozed_until
+                .map(|timestamp| Utc.timestamp_opt(timestamp, 0).unwrap()),
+            opened_at: value
+                .opened_at
+                .map(|timestamp| Utc.timestamp_opt(timestamp, 0).unwrap()),
+            first_staff_reply_at: value
+                .first_staff_reply_at
+                .map(|timestamp| Utc.timestamp_opt(timestamp, 0).unwrap()),
+            sla_alerted: value.sla_alerted,
+            category: value.category,
         })
     }
 }
@@ -29,14 +114,31 @@ impl Room {
         codename: String,
         channel_id: ChannelId,
         user_id: UserId,
+    ) -> Result<Self> {
+        Self::with_category(pool, codename, channel_id, user_id, None).await
+    }
+
+    /// Opens a room in `category`, next to any rooms the user has in other categories.
+    pub async fn with_category(
+        pool: &SqlitePool,
+        codename: String,
+        channel_id: ChannelId,
+        user_id: UserId,
+        category: Option<String>,
     ) -> Result<Self> {
         // HACK: query!() drops temporaries for some reason, must pass reference
         let (channel_str, user_str) = (&channel_id.to_string(), &user_id.to_string());
+        let last_activity_at = Utc::now();
+        let timestamp = last_activity_at.timestamp();
         let room_id = sqlx::query!(
-            "INSERT INTO rooms (codename, channel_id, user_id) VALUES (?, ?, ?) RETURNING room_id",
+            "INSERT INTO rooms (codename, channel_id, user_id, last_activity_at, opened_at, category)
+            VALUES (?, ?, ?, ?, ?, ?) RETURNING room_id",
             codename,
             channel_str,
-            user_str
+            user_str,
+            timestamp,
+            timestamp,
+            category
         )
         .fetch_one(pool)
         .await
@@ -48,9 +150,49 @@ impl Room {
             codename,
             channel_id,
             user_id,
+            last_activity_at,
+            archived: false,
+            priority: 0,
+            claimed_by: None,
+            inbox_id: None,
+            pause: None,
+            snoozed_until: None,
+            opened_at: Some(last_activity_at),
+            first_staff_reply_at: None,
+            sla_alerted: false,
+            category,
         })
     }
 
+    /// All rooms, most urgent first and then most recently active.
+    pub async fn all(pool: &SqlitePool) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            RawRoom,
+            "SELECT * FROM rooms ORDER BY priority DESC, last_activity_at DESC"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rt| Room::try_from(rt).expect("got malformed Room object from database"))
+        .collect())
+    }
+
+    /// Rooms belonging to a user who also has a more recent room in the same category.
+    pub async fn stale_duplicates(pool: &SqlitePool) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            RawRoom,
+            "SELECT * FROM rooms WHERE room_id NOT IN
+            (SELECT MAX(room_id) FROM rooms GROUP BY user_id, IFNULL(category, ''))"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rt| Room::try_from(rt).expect("got malformed Room object from database"))
+        .collect())
+    }
+
     pub async fn get_by_codename(pool: &SqlitePool, codename: &str) -> Result<Option<Self>> {
         Ok(
             sqlx::query_as!(RawRoom, "SELECT * FROM rooms WHERE codename = ?", codename)
@@ -73,11 +215,9 @@ impl Room {
         )
     }
 
-    pub async fn get_by_user(pool: &SqlitePool, user_id: UserId) -> Result<Option<Self>> {
-        // HACK: query!() drops temporaries for some reason, must pass reference
-        let temp = &user_id.to_string();
+    pub async fn get(pool: &SqlitePool, room_id: i64) -> Result<Option<Self>> {
         Ok(
-            sqlx::query_as!(RawRoom, "SELECT * FROM rooms WHERE user_id = ?", temp)
+            sqlx::query_as!(RawRoom, "SELECT * FROM rooms WHERE room_id = ?", room_id)
                 .fetch_optional(pool)
                 .await
                 .map_err(anyhow::Error::from)?
@@ -85,6 +225,323 @@ impl Room {
         )
     }
 
+    /// The user's room, or their most recently active one if they have rooms in several
+    /// categories.
+    pub async fn get_by_user(pool: &SqlitePool, user_id: UserId) -> Result<Option<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        Ok(sqlx::query_as!(
+            RawRoom,
+            "SELECT * FROM rooms WHERE user_id = ? ORDER BY last_activity_at DESC LIMIT 1",
+            temp
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(|rt| Room::try_from(rt).expect("got malformed Room object from database")))
+    }
+
+    /// The user's room in `category`, where `None` is the room from before categories.
+    pub async fn get_by_user_and_category(
+        pool: &SqlitePool,
+        user_id: UserId,
+        category: Option<&str>,
+    ) -> Result<Option<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        Ok(sqlx::query_as!(
+            RawRoom,
+            "SELECT * FROM rooms WHERE user_id = ? AND category IS ?",
+            temp,
+            category
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(|rt| Room::try_from(rt).expect("got malformed Room object from database")))
+    }
+
+    /// All of the user's rooms, one per category.
+    pub async fn for_user(pool: &SqlitePool, user_id: UserId) -> Result<Vec<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        Ok(sqlx::query_as!(
+            RawRoom,
+            "SELECT * FROM rooms WHERE user_id = ? ORDER BY room_id",
+            temp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rt| Room::try_from(rt).expect("got malformed Room object from database"))
+        .collect())
+    }
+
+    /// Rooms opened before `opened_before` that staff haven't replied in yet, and nobody was
+    /// alerted about.
+    pub async fn unanswered(pool: &SqlitePool, opened_before: DateTime<Utc>) -> Result<Vec<Self>> {
+        let timestamp = opened_before.timestamp();
+        Ok(sqlx::query_as!(
+            RawRoom,
+            "SELECT * FROM rooms
+            WHERE first_staff_reply_at IS NULL AND NOT sla_alerted AND opened_at <= ?",
+            timestamp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rt| Room::try_from(rt).expect("got malformed Room object from database"))
+        .collect())
+    }
+
+    /// How many rooms there are, i.e. open threads.
+    pub async fn count(pool: &SqlitePool) -> Result<i64> {
+        Ok(sqlx::query!("SELECT COUNT(*) AS count FROM rooms")
+            .fetch_one(pool)
+            .await
+            .map_err(anyhow::Error::from)?
+            .count
+            .into())
+    }
+
+    /// How many rooms have their thread in `inbox`.
+    pub async fn count_in_inbox(pool: &SqlitePool, inbox: ChannelId) -> Result<i64> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &inbox.to_string();
+        Ok(sqlx::query!(
+            "SELECT COUNT(*) AS count FROM rooms WHERE inbox_id = ?",
+            temp
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .count
+        .into())
+    }
+
+    pub async fn codename_exists(pool: &SqlitePool, codename: &str) -> Result<bool> {
+        // HACK: macro doesn't work, treats EXISTS() as a column name
+        let (exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM rooms WHERE codename = ?)")
+                .bind(codename)
+                .fetch_one(pool)
+                .await
+                .map_err(anyhow::Error::from)?;
+        Ok(exists)
+    }
+
+    /// Records that a message was just forwarded in this room.
+    pub async fn touch(&mut self, pool: &SqlitePool) -> Result<()> {
+        let now = Utc::now();
+        let timestamp = now.timestamp();
+        sqlx::query!(
+            "UPDATE rooms SET last_activity_at = ? WHERE room_id = ?",
+            timestamp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.last_activity_at = now;
+        Ok(())
+    }
+
+    pub async fn set_codename(&mut self, pool: &SqlitePool, codename: String) -> Result<()> {
+        sqlx::query!(
+            "UPDATE rooms SET codename = ? WHERE room_id = ?",
+            codename,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.codename = codename;
+        Ok(())
+    }
+
+    pub async fn set_archived(&mut self, pool: &SqlitePool, archived: bool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE rooms SET archived = ? WHERE room_id = ?",
+            archived,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.archived = archived;
+        Ok(())
+    }
+
+    pub async fn set_priority(&mut self, pool: &SqlitePool, priority: i64) -> Result<()> {
+        sqlx::query!(
+            "UPDATE rooms SET priority = ? WHERE room_id = ?",
+            priority,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.priority = priority;
+        Ok(())
+    }
+
+    pub async fn set_claimed_by(
+        &mut self,
+        pool: &SqlitePool,
+        claimed_by: Option<UserId>,
+    ) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &claimed_by.map(|id| id.to_string());
+        sqlx::query!(
+            "UPDATE rooms SET claimed_by = ? WHERE room_id = ?",
+            temp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.claimed_by = claimed_by;
+        Ok(())
+    }
+
+    /// Hands every room claimed by `from` to `to`, or leaves them unclaimed. Returns how many
+    /// rooms were affected.
+    pub async fn reassign_claims(
+        pool: &SqlitePool,
+        from: UserId,
+        to: Option<UserId>,
+    ) -> Result<u64> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let (from_str, to_str) = (&from.to_string(), &to.map(|id| id.to_string()));
+        let res = sqlx::query!(
+            "UPDATE rooms SET claimed_by = ? WHERE claimed_by = ?",
+            to_str,
+            from_str
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn set_inbox_id(&mut self, pool: &SqlitePool, inbox_id: ChannelId) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &inbox_id.to_string();
+        sqlx::query!(
+            "UPDATE rooms SET inbox_id = ? WHERE room_id = ?",
+            temp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.inbox_id = Some(inbox_id);
+        Ok(())
+    }
+
+    /// Rebinds the room to another thread, e.g. after moving it to a new inbox.
+    pub async fn update_channel(&mut self, pool: &SqlitePool, channel_id: ChannelId) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &channel_id.to_string();
+        sqlx::query!(
+            "UPDATE rooms SET channel_id = ? WHERE room_id = ?",
+            temp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.channel_id = channel_id;
+        Ok(())
+    }
+
+    pub async fn set_pause(&mut self, pool: &SqlitePool, pause: Option<Pause>) -> Result<()> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &pause.map(|pause| pause.to_string());
+        sqlx::query!(
+            "UPDATE rooms SET pause = ? WHERE room_id = ?",
+            temp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.pause = pause;
+        Ok(())
+    }
+
+    pub async fn set_snoozed_until(
+        &mut self,
+        pool: &SqlitePool,
+        snoozed_until: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let timestamp = snoozed_until.map(|until| until.timestamp());
+        sqlx::query!(
+            "UPDATE rooms SET snoozed_until = ? WHERE room_id = ?",
+            timestamp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.snoozed_until = snoozed_until;
+        Ok(())
+    }
+
+    /// Records when staff first replied to the user, unless they did already.
+    pub async fn set_first_staff_reply_at(
+        &mut self,
+        pool: &SqlitePool,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        if self.first_staff_reply_at.is_some() {
+            return Ok(());
+        }
+
+        let timestamp = at.timestamp();
+        sqlx::query!(
+            "UPDATE rooms SET first_staff_reply_at = ?
+            WHERE room_id = ? AND first_staff_reply_at IS NULL",
+            timestamp,
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.first_staff_reply_at = Some(at);
+        Ok(())
+    }
+
+    pub async fn set_sla_alerted(&mut self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE rooms SET sla_alerted = TRUE WHERE room_id = ?",
+            self.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.sla_alerted = true;
+        Ok(())
+    }
+
+    /// Whether the room is hidden from `/list` at `now`.
+    pub fn snoozed(&self, now: DateTime<Utc>) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now)
+    }
+
     pub async fn delete(self, pool: &SqlitePool) -> Result<()> {
         sqlx::query!("DELETE FROM rooms WHERE room_id = ?", self.room_id)
             .execute(pool)
@@ -100,4 +557,190 @@ struct RawRoom {
     codename: String,
     channel_id: String,
     user_id: String,
+    last_activity_at: i64,
+    archived: bool,
+    priority: i64,
+    claimed_by: Option<String>,
+    inbox_id: Option<String>,
+    pause: Option<String>,
+    snoozed_until: Option<i64>,
+    opened_at: Option<i64>,
+    first_staff_reply_at: Option<i64>,
+    sla_alerted: bool,
+    category: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pause, Room};
+    use chrono::{Duration, Utc};
+    use sqlx::SqlitePool;
+
+    #[tokio::test]
+    async fn room_lifecycle() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        let mut room = Room::new(&pool, "quiet owl".into(), 1.into(), 2.into())
+            .await
+            .unwrap();
+        assert!(Utc::now() - room.last_activity_at < Duration::minutes(1));
+
+        // Touch
+        sqlx::query!("UPDATE rooms SET last_activity_at = 0")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let stale = Room::get_by_user(&pool, 2.into()).await.unwrap().unwrap();
+        assert_eq!(stale.last_activity_at.timestamp(), 0);
+
+        room.touch(&pool).await.unwrap();
+        let fresh = Room::get_by_user(&pool, 2.into()).await.unwrap().unwrap();
+        assert_eq!(
+            fresh.last_activity_at.timestamp(),
+            room.last_activity_at.timestamp()
+        );
+
+        // Rename
+        let mut room = fresh;
+        room.set_codename(&pool, "quiet bird".into()).await.unwrap();
+        assert!(Room::codename_exists(&pool, "quiet bird").await.unwrap());
+        assert!(!Room::codename_exists(&pool, "quiet owl").await.unwrap());
+
+        // Archive
+        assert!(!room.archived);
+        room.set_archived(&pool, true).await.unwrap();
+        let archived = Room::get_by_user(&pool, 2.into()).await.unwrap().unwrap();
+        assert!(archived.archived);
+
+        // Prioritize
+        let mut urgent = Room::new(&pool, "loud owl".into(), 3.into(), 4.into())
+            .await
+            .unwrap();
+        urgent.set_priority(&pool, 2).await.unwrap();
+        let codenames: Vec<_> = Room::all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|room| room.codename)
+            .collect();
+        assert_eq!(codenames, ["loud owl", "quiet bird"]);
+
+        // Claim
+        urgent.set_claimed_by(&pool, Some(5.into())).await.unwrap();
+        let claimed = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(claimed.claimed_by, Some(5.into()));
+        assert_eq!(
+            Room::reassign_claims(&pool, 5.into(), Some(7.into()))
+                .await
+                .unwrap(),
+            1
+        );
+        let reassigned = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(reassigned.claimed_by, Some(7.into()));
+        assert_eq!(
+            Room::reassign_claims(&pool, 5.into(), None).await.unwrap(),
+            0
+        );
+        urgent.set_claimed_by(&pool, None).await.unwrap();
+        let unclaimed = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(unclaimed.claimed_by, None);
+
+        // Inbox
+        assert_eq!(Room::count(&pool).await.unwrap(), 2);
+        assert_eq!(Room::count_in_inbox(&pool, 6.into()).await.unwrap(), 0);
+        urgent.set_inbox_id(&pool, 6.into()).await.unwrap();
+        assert_eq!(Room::count_in_inbox(&pool, 6.into()).await.unwrap(), 1);
+        let moved = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(moved.inbox_id, Some(6.into()));
+        urgent.update_channel(&pool, 7.into()).await.unwrap();
+        let rebound = Room::get_by_channel(&pool, 7.into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rebound.room_id, urgent.room_id);
+
+        // Pause
+        let mut paused = moved;
+        paused.set_pause(&pool, Some(Pause::Drop)).await.unwrap();
+        let got = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(got.pause, Some(Pause::Drop));
+        paused.set_pause(&pool, None).await.unwrap();
+        let got = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(got.pause, None);
+
+        // Snooze
+        let mut snoozed = got;
+        let until = Utc::now() + Duration::hours(1);
+        snoozed.set_snoozed_until(&pool, Some(until)).await.unwrap();
+        let got = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert_eq!(got.snoozed_until.unwrap().timestamp(), until.timestamp());
+        assert!(got.snoozed(Utc::now()));
+        assert!(!got.snoozed(until));
+        snoozed.set_snoozed_until(&pool, None).await.unwrap();
+        let got = Room::get_by_user(&pool, 4.into()).await.unwrap().unwrap();
+        assert!(!got.snoozed(Utc::now()));
+
+        // Categorize
+        assert!(Room::new(&pool, "calm owl".into(), 8.into(), 4.into())
+            .await
+            .is_err());
+        let billing = Room::with_category(
+            &pool,
+            "calm owl".into(),
+            8.into(),
+            4.into(),
+            Some("billing".into()),
+        )
+        .await
+        .unwrap();
+        let rooms: Vec<_> = Room::for_user(&pool, 4.into())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|room| room.category)
+            .collect();
+        assert_eq!(rooms, [None, Some("billing".into())]);
+        let got = Room::get_by_user_and_category(&pool, 4.into(), Some("billing"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(got.room_id, billing.room_id);
+        let got = Room::get_by_user_and_category(&pool, 4.into(), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(got.room_id, urgent.room_id);
+        assert!(Room::stale_duplicates(&pool).await.unwrap().is_empty());
+        assert_eq!(
+            Room::get(&pool, billing.room_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .codename,
+            "calm owl"
+        );
+        billing.delete(&pool).await.unwrap();
+
+        // Unanswered
+        let later = Utc::now() + Duration::minutes(1);
+        assert_eq!(Room::unanswered(&pool, later).await.unwrap().len(), 2);
+        let mut alerted = got;
+        alerted.set_sla_alerted(&pool).await.unwrap();
+        let mut answered = Room::get_by_user(&pool, 2.into()).await.unwrap().unwrap();
+        answered
+            .set_first_staff_reply_at(&pool, Utc::now())
+            .await
+            .unwrap();
+        assert!(Room::unanswered(&pool, later).await.unwrap().is_empty());
+        let earlier = Utc::now() - Duration::minutes(1);
+        assert!(Room::unanswered(&pool, earlier).await.unwrap().is_empty());
+
+        // Delete
+        urgent.delete(&pool).await.unwrap();
+        archived.delete(&pool).await.unwrap();
+        assert!(Room::get_by_user(&pool, 2.into()).await.unwrap().is_none());
+    }
 }
@@ -0,0 +1,236 @@
+use crate::error::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serenity::model::id::UserId;
+use sqlx::{FromRow, SqlitePool};
+use std::{num::ParseIntError, result::Result as StdResult};
+
+use super::rooms::Room;
+
+/// A message for a room's user, to be sent at a later time with `/schedule`.
+pub struct ScheduledMessage {
+    pub scheduled_id: i64,
+    /// The room whose user the message goes to.
+    pub room_id: i64,
+    /// The staff member who scheduled it.
+    pub author_id: UserId,
+    pub content: String,
+    pub due_at: DateTime<Utc>,
+}
+
+impl TryFrom<RawScheduledMessage> for ScheduledMessage {
+    type Error = ParseIntError;
+
+    fn try_from(value: RawScheduledMessage) -> StdResult<Self, Self::Error> {
+        Ok(Self {
+            scheduled_id: value.scheduled_id,
+            room_id: value.room_id,
+            author_id: value.author_id.parse::<u64>()?.into(),
+            content: value.content,
+            due_at: Utc.timestamp_opt(value.due_at, 0).unwrap(),
+        })
+    }
+}
+
+impl ScheduledMessage {
+    pub async fn new(
+        pool: &SqlitePool,
+        room: &Room,
+        author_id: UserId,
+        content: String,
+        due_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let (author_str, timestamp) = (&author_id.to_string(), due_at.timestamp());
+        let scheduled_id = sqlx::query!(
+            "INSERT INTO scheduled_messages
+            (room_id, author_id, content, due_at) VALUES (?, ?, ?, ?)
+            RETURNING scheduled_id",
+            room.room_id,
+            author_str,
+            content,
+            timestamp
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .scheduled_id;
+
+        Ok(Self {
+            scheduled_id,
+            room_id: room.room_id,
+            author_id,
+            content,
+            due_at,
+        })
+    }
+
+    pub async fn get(pool: &SqlitePool, scheduled_id: i64) -> Result<Option<Self>> {
+        Ok(sqlx::query_as!(
+            RawScheduledMessage,
+            "SELECT scheduled_id, room_id, author_id, content, due_at FROM scheduled_messages
+            WHERE scheduled_id = ?",
+            scheduled_id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(|rs| {
+            ScheduledMessage::try_from(rs).expect("got malformed ScheduledMessage from database")
+        }))
+    }
+
+    /// A room's pending messages, soonest first.
+    pub async fn for_room(pool: &SqlitePool, room_id: i64) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            RawScheduledMessage,
+            "SELECT scheduled_id, room_id, author_id, content, due_at FROM scheduled_messages
+            WHERE room_id = ? ORDER BY due_at",
+            room_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rs| {
+            ScheduledMessage::try_from(rs).expect("got malformed ScheduledMessage from database")
+        })
+        .collect())
+    }
+
+    /// Messages due at or before `now`, oldest first.
+    pub async fn due(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<Self>> {
+        let timestamp = now.timestamp();
+        Ok(sqlx::query_as!(
+            RawScheduledMessage,
+            "SELECT scheduled_id, room_id, author_id, content, due_at FROM scheduled_messages
+            WHERE due_at <= ? ORDER BY due_at",
+            timestamp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(|rs| {
+            ScheduledMessage::try_from(rs).expect("got malformed ScheduledMessage from database")
+        })
+        .collect())
+    }
+
+    /// Moves the scheduled messages of `from` over to `to`, e.g. before `from` is merged into
+    /// it. Returns how many messages were moved.
+    pub async fn move_all(pool: &SqlitePool, from: &Room, to: &Room) -> Result<u64> {
+        let res = sqlx::query!(
+            "UPDATE scheduled_messages SET room_id = ? WHERE room_id = ?",
+            to.room_id,
+            from.room_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn delete(self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM scheduled_messages WHERE scheduled_id = ?",
+            self.scheduled_id
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
+
+#[derive(FromRow)]
+struct RawScheduledMessage {
+    scheduled_id: i64,
+    room_id: i64,
+    author_id: String,
+    content: String,
+    due_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use sqlx::SqlitePool;
+
+    use super::{Room, ScheduledMessage};
+
+    #[tokio::test]
+    async fn scheduled_message_crud() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let room = Room::new(&pool, "quiet owl".into(), 1.into(), 2.into())
+            .await
+            .unwrap();
+
+        // Create
+        let now = Utc::now();
+        let soon = ScheduledMessage::new(&pool, &room, 3.into(), "hi".into(), now)
+            .await
+            .unwrap();
+        ScheduledMessage::new(
+            &pool,
+            &room,
+            3.into(),
+            "still there?".into(),
+            now + Duration::hours(1),
+        )
+        .await
+        .unwrap();
+
+        // Get
+        let got = ScheduledMessage::get(&pool, soon.scheduled_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(got.author_id, 3);
+        assert_eq!(got.content, "hi");
+        assert_eq!(
+            ScheduledMessage::for_room(&pool, room.room_id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        let due = ScheduledMessage::due(&pool, now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].room_id, room.room_id);
+
+        // Delete
+        soon.delete(&pool).await.unwrap();
+        assert!(ScheduledMessage::due(&pool, now).await.unwrap().is_empty());
+
+        // Move
+        let other = Room::with_category(
+            &pool,
+            "loud crow".into(),
+            4.into(),
+            2.into(),
+            Some("billing".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            ScheduledMessage::move_all(&pool, &room, &other)
+                .await
+                .unwrap(),
+            1
+        );
+        room.delete(&pool).await.unwrap();
+        let later = now + Duration::days(1);
+        let due = ScheduledMessage::due(&pool, later).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].room_id, other.room_id);
+
+        // closing the room drops its scheduled messages
+        other.delete(&pool).await.unwrap();
+        assert!(ScheduledMessage::due(&pool, later)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
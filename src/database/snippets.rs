@@ -0,0 +1,158 @@
+use crate::error::Result;
+use sqlx::SqlitePool;
+
+/// A canned response staff can send with `/reply`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Snippet {
+    pub name: String,
+    /// The response, which may contain `{codename}` and `{user}` placeholders.
+    pub content: String,
+    /// Sends the snippet when staff react with it to a thread's message in the inbox.
+    pub emoji: Option<String>,
+}
+
+impl Snippet {
+    pub async fn new(pool: &SqlitePool, name: String, content: String) -> Result<Self> {
+        sqlx::query!(
+            "INSERT INTO snippets (name, content) VALUES (?, ?)",
+            name,
+            content
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        Ok(Self {
+            name,
+            content,
+            emoji: None,
+        })
+    }
+
+    pub async fn all(pool: &SqlitePool) -> Result<Vec<Self>> {
+        Ok(
+            sqlx::query_as!(Snippet, "SELECT * FROM snippets ORDER BY name")
+                .fetch_all(pool)
+                .await
+                .map_err(anyhow::Error::from)?,
+        )
+    }
+
+    pub async fn get(pool: &SqlitePool, name: &str) -> Result<Option<Self>> {
+        Ok(
+            sqlx::query_as!(Snippet, "SELECT * FROM snippets WHERE name = ?", name)
+                .fetch_optional(pool)
+                .await
+                .map_err(anyhow::Error::from)?,
+        )
+    }
+
+    pub async fn get_by_emoji(pool: &SqlitePool, emoji: &str) -> Result<Option<Self>> {
+        Ok(
+            sqlx::query_as!(Snippet, "SELECT * FROM snippets WHERE emoji = ?", emoji)
+                .fetch_optional(pool)
+                .await
+                .map_err(anyhow::Error::from)?,
+        )
+    }
+
+    pub async fn set_content(&mut self, pool: &SqlitePool, content: String) -> Result<()> {
+        sqlx::query!(
+            "UPDATE snippets SET content = ? WHERE name = ?",
+            content,
+            self.name
+        )
+        .execute(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+        self.content = content;
+        Ok(())
+    }
+
+    /// Binds `emoji` to this snippet, taking it away from any other snippet, or unbinds it.
+    pub async fn set_emoji(&mut self, pool: &SqlitePool, emoji: Option<String>) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(anyhow::Error::from)?;
+        sqlx::query!("UPDATE snippets SET emoji = NULL WHERE emoji = ?", emoji)
+            .execute(&mut tx)
+            .await
+            .map_err(anyhow::Error::from)?;
+        sqlx::query!(
+            "UPDATE snippets SET emoji = ? WHERE name = ?",
+            emoji,
+            self.name
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(anyhow::Error::from)?;
+        tx.commit().await.map_err(anyhow::Error::from)?;
+
+        self.emoji = emoji;
+        Ok(())
+    }
+
+    /// Returns whether a snippet with the given name existed.
+    pub async fn delete(pool: &SqlitePool, name: &str) -> Result<bool> {
+        let res = sqlx::query!("DELETE FROM snippets WHERE name = ?", name)
+            .execute(pool)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::Snippet;
+
+    #[tokio::test]
+    async fn snippet_crud() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        Snippet::new(&pool, "hello".into(), "Hi {user}!".into())
+            .await
+            .unwrap();
+        Snippet::new(&pool, "bye".into(), "Closing `{codename}`.".into())
+            .await
+            .unwrap();
+        assert!(Snippet::new(&pool, "hello".into(), "again".into())
+            .await
+            .is_err());
+
+        // Get
+        let names: Vec<_> = Snippet::all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["bye", "hello"]);
+        assert_eq!(Snippet::get(&pool, "nope").await.unwrap(), None);
+
+        // Update
+        let mut hello = Snippet::get(&pool, "hello").await.unwrap().unwrap();
+        hello
+            .set_content(&pool, "Hello {user}!".into())
+            .await
+            .unwrap();
+        assert_eq!(Snippet::get(&pool, "hello").await.unwrap(), Some(hello));
+
+        // Emoji
+        let mut hello = Snippet::get(&pool, "hello").await.unwrap().unwrap();
+        hello.set_emoji(&pool, Some("👋".into())).await.unwrap();
+        let mut bye = Snippet::get(&pool, "bye").await.unwrap().unwrap();
+        bye.set_emoji(&pool, Some("👋".into())).await.unwrap();
+        assert_eq!(Snippet::get_by_emoji(&pool, "👋").await.unwrap(), Some(bye));
+        let hello = Snippet::get(&pool, "hello").await.unwrap().unwrap();
+        assert_eq!(hello.emoji, None);
+
+        // Delete
+        assert!(Snippet::delete(&pool, "bye").await.unwrap());
+        assert!(!Snippet::delete(&pool, "bye").await.unwrap());
+    }
+}
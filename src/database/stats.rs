@@ -0,0 +1,175 @@
+use crate::error::Result;
+use chrono::{DateTime, Duration, TimeZone, Timelike, Utc};
+use sqlx::SqlitePool;
+
+use super::rooms::Room;
+
+/// How many of the busiest hours a report lists.
+const BUSIEST_HOURS: usize = 3;
+
+/// Keeps the timings of a closed room for `/report`, leaving out who its user was.
+pub async fn record_close(pool: &SqlitePool, room: &Room, closed_at: DateTime<Utc>) -> Result<()> {
+    let (opened, replied, closed) = (
+        room.opened_at.map(|at| at.timestamp()),
+        room.first_staff_reply_at.map(|at| at.timestamp()),
+        closed_at.timestamp(),
+    );
+    sqlx::query!(
+        "INSERT INTO thread_stats (opened_at, first_staff_reply_at, closed_at) VALUES (?, ?, ?)",
+        opened,
+        replied,
+        closed
+    )
+    .execute(pool)
+    .await
+    .map_err(anyhow::Error::from)?;
+    Ok(())
+}
+
+/// What happened in threads over a period of time, see `/report`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Report {
+    pub opened: usize,
+    pub closed: usize,
+    /// The median time from a thread being opened to staff first replying.
+    pub median_first_response: Option<Duration>,
+    /// Hours of the day in UTC in which the most threads were opened, with how many were.
+    pub busiest_hours: Vec<(u32, usize)>,
+}
+
+impl Report {
+    pub async fn since(pool: &SqlitePool, since: DateTime<Utc>) -> Result<Self> {
+        let timestamp = since.timestamp();
+        let open = sqlx::query!(
+            "SELECT opened_at, first_staff_reply_at FROM rooms WHERE opened_at >= ?",
+            timestamp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        let closed = sqlx::query!(
+            "SELECT opened_at, first_staff_reply_at FROM thread_stats WHERE opened_at >= ?",
+            timestamp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+        let closed_count = sqlx::query!(
+            "SELECT COUNT(*) AS count FROM thread_stats WHERE closed_at >= ?",
+            timestamp
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .count;
+
+        let threads: Vec<_> = open
+            .into_iter()
+            .map(|row| (row.opened_at, row.first_staff_reply_at))
+            .chain(
+                closed
+                    .into_iter()
+                    .map(|row| (row.opened_at, row.first_staff_reply_at)),
+            )
+            .filter_map(|(opened, replied)| {
+                let opened = Utc.timestamp_opt(opened?, 0).unwrap();
+                let replied = replied.map(|at| Utc.timestamp_opt(at, 0).unwrap());
+                Some((opened, replied))
+            })
+            .collect();
+        Ok(Self::from_threads(&threads, closed_count as usize))
+    }
+
+    /// Summarizes threads given as when they were opened and first replied to.
+    fn from_threads(threads: &[(DateTime<Utc>, Option<DateTime<Utc>>)], closed: usize) -> Self {
+        let mut responses: Vec<_> = threads
+            .iter()
+            .filter_map(|(opened, replied)| Some((*replied)? - *opened))
+            .collect();
+        responses.sort();
+        let median_first_response = match responses.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(responses[len / 2]),
+            len => Some((responses[len / 2 - 1] + responses[len / 2]) / 2),
+        };
+
+        let mut hours = [0; 24];
+        for (opened, _) in threads {
+            hours[opened.hour() as usize] += 1;
+        }
+        let mut busiest_hours: Vec<_> = (0..24)
+            .map(|hour| (hour, hours[hour as usize]))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        busiest_hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        busiest_hours.truncate(BUSIEST_HOURS);
+
+        Self {
+            opened: threads.len(),
+            closed,
+            median_first_response,
+            busiest_hours,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use sqlx::SqlitePool;
+
+    use super::{record_close, Report, Room};
+
+    #[test]
+    fn report_from_threads() {
+        let at = |hour, minute| Utc.with_ymd_and_hms(2022, 9, 1, hour, minute, 0).unwrap();
+        let threads = [
+            (at(9, 0), Some(at(9, 10))),
+            (at(9, 30), Some(at(10, 0))),
+            (at(14, 0), None),
+            (at(14, 5), Some(at(14, 25))),
+            (at(20, 0), Some(at(20, 40))),
+        ];
+        let report = Report::from_threads(&threads, 2);
+        assert_eq!(report.opened, 5);
+        assert_eq!(report.closed, 2);
+        assert_eq!(report.median_first_response, Some(Duration::minutes(25)));
+        assert_eq!(report.busiest_hours, [(9, 2), (14, 2), (20, 1)]);
+
+        let empty = Report::from_threads(&[], 0);
+        assert_eq!(empty.median_first_response, None);
+        assert!(empty.busiest_hours.is_empty());
+    }
+
+    #[tokio::test]
+    async fn report_since() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let since = Utc::now() - Duration::days(1);
+
+        // Create
+        let mut open = Room::new(&pool, "quiet owl".into(), 1.into(), 2.into())
+            .await
+            .unwrap();
+        open.set_first_staff_reply_at(&pool, Utc::now())
+            .await
+            .unwrap();
+        let closed = Room::new(&pool, "loud owl".into(), 3.into(), 4.into())
+            .await
+            .unwrap();
+        record_close(&pool, &closed, Utc::now()).await.unwrap();
+        closed.delete(&pool).await.unwrap();
+
+        // Get
+        let report = Report::since(&pool, since).await.unwrap();
+        assert_eq!(report.opened, 2);
+        assert_eq!(report.closed, 1);
+        assert!(report.median_first_response.unwrap() < Duration::minutes(1));
+
+        let later = Report::since(&pool, Utc::now() + Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!((later.opened, later.closed), (0, 0));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::error::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serenity::model::id::UserId;
+use sqlx::{FromRow, SqlitePool};
+
+/// The messages of a closed room, kept for future reference.
+pub struct Transcript {
+    pub transcript_id: i64,
+    pub codename: String,
+    pub user_id: UserId,
+    pub closed_at: DateTime<Utc>,
+}
+
+impl From<RawTranscript> for Transcript {
+    fn from(value: RawTranscript) -> Self {
+        Self {
+            transcript_id: value.transcript_id,
+            codename: value.codename,
+            user_id: value
+                .user_id
+                .parse::<u64>()
+                .expect("got malformed Transcript from database")
+                .into(),
+            closed_at: Utc.timestamp_opt(value.closed_at, 0).unwrap(),
+        }
+    }
+}
+
+impl Transcript {
+    pub async fn new(
+        pool: &SqlitePool,
+        codename: &str,
+        user_id: UserId,
+        content: &str,
+    ) -> Result<Self> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let closed_at = Utc::now();
+        let (user_str, timestamp) = (&user_id.to_string(), closed_at.timestamp());
+        let transcript_id = sqlx::query!(
+            "INSERT INTO transcripts (codename, user_id, closed_at, content) VALUES (?, ?, ?, ?)
+            RETURNING transcript_id",
+            codename,
+            user_str,
+            timestamp,
+            content
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .transcript_id;
+
+        Ok(Self {
+            transcript_id,
+            codename: codename.to_owned(),
+            user_id,
+            closed_at,
+        })
+    }
+
+    pub async fn get(pool: &SqlitePool, transcript_id: i64) -> Result<Option<Self>> {
+        Ok(sqlx::query_as!(
+            RawTranscript,
+            "SELECT transcript_id, codename, user_id, closed_at FROM transcripts
+            WHERE transcript_id = ?",
+            transcript_id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .map(Transcript::from))
+    }
+
+    /// All transcripts of a user's threads, most recently closed first.
+    pub async fn for_user(pool: &SqlitePool, user_id: UserId) -> Result<Vec<Self>> {
+        // HACK: query!() drops temporaries for some reason, must pass reference
+        let temp = &user_id.to_string();
+        Ok(sqlx::query_as!(
+            RawTranscript,
+            "SELECT transcript_id, codename, user_id, closed_at FROM transcripts
+            WHERE user_id = ? ORDER BY closed_at DESC, transcript_id DESC",
+            temp
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .into_iter()
+        .map(Transcript::from)
+        .collect())
+    }
+
+    pub async fn content(&self, pool: &SqlitePool) -> Result<String> {
+        Ok(sqlx::query!(
+            "SELECT content FROM transcripts WHERE transcript_id = ?",
+            self.transcript_id
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(anyhow::Error::from)?
+        .content)
+    }
+}
+
+#[derive(FromRow)]
+struct RawTranscript {
+    transcript_id: i64,
+    codename: String,
+    user_id: String,
+    closed_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use super::Transcript;
+
+    #[tokio::test]
+    async fn transcripts() {
+        // Setup
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // Create
+        let first = Transcript::new(&pool, "loud owl", 1.into(), "hello")
+            .await
+            .unwrap();
+        let second = Transcript::new(&pool, "quiet owl", 1.into(), "hello again")
+            .await
+            .unwrap();
+        Transcript::new(&pool, "other owl", 2.into(), "hi")
+            .await
+            .unwrap();
+
+        // Get
+        let history = Transcript::for_user(&pool, 1.into()).await.unwrap();
+        let ids: Vec<_> = history.iter().map(|t| t.transcript_id).collect();
+        assert_eq!(ids, [second.transcript_id, first.transcript_id]);
+
+        let got = Transcript::get(&pool, first.transcript_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(got.codename, "loud owl");
+        assert_eq!(got.user_id, 1);
+        assert_eq!(got.content(&pool).await.unwrap(), "hello");
+        assert!(Transcript::get(&pool, 1000).await.unwrap().is_none());
+    }
+}
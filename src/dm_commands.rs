@@ -0,0 +1,33 @@
+/// Splits a DM invoking `command` into the argument on its first line and the rest of the
+/// message, e.g. `!codename night owl\nhello` gives `("night owl", "hello")`.
+///
+/// Returns `None` if the message doesn't start with `command`.
+pub fn split<'a>(content: &'a str, command: &str) -> Option<(&'a str, &'a str)> {
+    let (first, rest) = content.split_once('\n').unwrap_or((content, ""));
+    let argument = first.trim_start().strip_prefix(command)?;
+    if !argument.is_empty() && !argument.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    Some((argument.trim(), rest.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split;
+
+    #[test]
+    fn split_commands() {
+        assert_eq!(
+            split("!codename night owl", "!codename"),
+            Some(("night owl", ""))
+        );
+        assert_eq!(
+            split("!codename night owl\nhi, I need help", "!codename"),
+            Some(("night owl", "hi, I need help"))
+        );
+        assert_eq!(split("  !codename", "!codename"), Some(("", "")));
+        assert_eq!(split("!codenames", "!codename"), None);
+        assert_eq!(split("hello !codename owl", "!codename"), None);
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::Duration;
+
+/// Parses a duration such as `30m`, `2h` or `1d12h`.
+pub fn parse(s: &str) -> Result<Duration, String> {
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let amount: i64 = digits
+            .parse()
+            .map_err(|_| format!("expected a number before `{}`", c))?;
+        digits.clear();
+
+        let unit = match c {
+            's' => Duration::try_seconds,
+            'm' => Duration::try_minutes,
+            'h' => Duration::try_hours,
+            'd' => Duration::try_days,
+            'w' => Duration::try_weeks,
+            _ => {
+                return Err(format!(
+                    "unknown unit `{}`, expected one of s, m, h, d or w",
+                    c
+                ))
+            }
+        };
+        total = unit(amount)
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(|| "duration is too long".to_owned())?;
+    }
+
+    if !digits.is_empty() {
+        return Err(format!("missing unit after `{}`", digits));
+    }
+    if total <= Duration::zero() {
+        return Err("expected a duration such as `30m` or `2h`".to_owned());
+    }
+    Ok(total)
+}
+
+/// Formats a duration the way [`parse`] reads it, e.g. `1d2h`, leaving out seconds unless
+/// it's shorter than a minute.
+pub fn format(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    if seconds < 60 {
+        return format!("{}s", seconds);
+    }
+
+    let mut out = String::new();
+    let minutes = seconds / 60;
+    for (amount, unit) in [
+        (minutes / (24 * 60), 'd'),
+        (minutes / 60 % 24, 'h'),
+        (minutes % 60, 'm'),
+    ] {
+        if amount > 0 {
+            out.push_str(&format!("{}{}", amount, unit));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::{format, parse};
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse("30m"), Ok(Duration::minutes(30)));
+        assert_eq!(parse("1d12h"), Ok(Duration::hours(36)));
+        assert_eq!(parse(" 2w "), Ok(Duration::weeks(2)));
+
+        assert!(parse("").is_err());
+        assert!(parse("0m").is_err());
+        assert!(parse("30").is_err());
+        assert!(parse("h").is_err());
+        assert!(parse("3y").is_err());
+        assert!(parse("99999999999999w").is_err());
+        // each part fits, but not their sum
+        assert!(parse("10000000000w10000000000w").is_err());
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format(Duration::seconds(45)), "45s");
+        assert_eq!(format(Duration::minutes(90)), "1h30m");
+        assert_eq!(format(Duration::hours(26)), "1d2h");
+        assert_eq!(
+            parse(&format(Duration::minutes(2000))),
+            Ok(Duration::minutes(2000))
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serenity::model::id::MessageId;
+
+/// Collapses rapid edits of the same message, so only the last one in a burst is forwarded.
+///
+/// Every edit is counted, and whoever handles an edit waits out the window before checking
+/// whether it's still the latest.
+#[derive(Default)]
+pub struct EditDebouncer(Mutex<HashMap<MessageId, u64>>);
+
+impl EditDebouncer {
+    /// Records an edit of `message`, returning which edit it was.
+    pub fn push(&self, message: MessageId) -> u64 {
+        let mut edits = self.0.lock().unwrap();
+        let count = edits.entry(message).or_default();
+        *count += 1;
+        *count
+    }
+
+    /// Whether `edit` is still the latest edit of `message`, forgetting the message if so.
+    pub fn settle(&self, message: MessageId, edit: u64) -> bool {
+        let mut edits = self.0.lock().unwrap();
+        match edits.get(&message) {
+            Some(&latest) if latest == edit => {
+                edits.remove(&message);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EditDebouncer;
+
+    #[test]
+    fn debounce_edits() {
+        let edits = EditDebouncer::default();
+        let first = edits.push(1.into());
+        let second = edits.push(1.into());
+        let other = edits.push(2.into());
+        assert!(!edits.settle(1.into(), first));
+        assert!(edits.settle(1.into(), second));
+        assert!(edits.settle(2.into(), other));
+
+        // a new burst starts over
+        let next = edits.push(1.into());
+        assert!(edits.settle(1.into(), next));
+    }
+}
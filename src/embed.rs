@@ -0,0 +1,57 @@
+/// Discord's limit on the length of an embed's description.
+pub const DESCRIPTION_LIMIT: usize = 4096;
+
+/// What an oversized description ends with, pointing at the full content.
+const TRUNCATED_MARKER: &str = "\n\n… (too long to show here, see the attached file)";
+
+/// Filename of the attachment carrying an oversized description in full.
+pub const OVERFLOW_FILENAME: &str = "response.txt";
+
+/// Shortens content that doesn't fit in an embed description, cutting at a line break where
+/// possible so list entries stay whole. Returns the description and, if it was shortened, the
+/// full content to attach instead.
+pub fn fit_description(content: String) -> (String, Option<String>) {
+    if content.chars().count() <= DESCRIPTION_LIMIT {
+        return (content, None);
+    }
+
+    let keep = DESCRIPTION_LIMIT - TRUNCATED_MARKER.chars().count();
+    let end = content
+        .char_indices()
+        .nth(keep)
+        .map_or(content.len(), |(i, _)| i);
+    let cut = match content[..end].rfind('\n') {
+        Some(i) => &content[..i],
+        None => &content[..end],
+    };
+
+    (format!("{}{}", cut, TRUNCATED_MARKER), Some(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fit_description, DESCRIPTION_LIMIT};
+
+    #[test]
+    fn fit_short_description() {
+        assert_eq!(fit_description("hi".into()), ("hi".into(), None));
+
+        let exact = "a".repeat(DESCRIPTION_LIMIT);
+        assert_eq!(fit_description(exact.clone()), (exact, None));
+    }
+
+    #[test]
+    fn fit_long_description() {
+        let lines: Vec<_> = (0..1000).map(|i| format!("entry {}", i)).collect();
+        let content = lines.join("\n");
+        let (desc, full) = fit_description(content.clone());
+        assert!(desc.chars().count() <= DESCRIPTION_LIMIT);
+        assert!(desc.starts_with("entry 0\nentry 1\n"));
+        assert!(desc.ends_with("see the attached file)"));
+        assert_eq!(full, Some(content));
+
+        // no line break to cut at, and multibyte characters mustn't be split
+        let (desc, _) = fit_description("ä".repeat(DESCRIPTION_LIMIT + 1));
+        assert!(desc.chars().count() <= DESCRIPTION_LIMIT);
+    }
+}
@@ -0,0 +1,45 @@
+use regex::Regex;
+use serenity::utils::MessageBuilder;
+
+const REDACTED: &str = "[redacted]";
+
+/// Redacts every match of `filters` from `content`, then escapes it for forwarding.
+///
+/// `@everyone` and `@here` are always neutralized, whether or not any filters are set.
+pub fn sanitize(content: &str, filters: &[Regex]) -> String {
+    let redacted = filters.iter().fold(content.to_owned(), |acc, filter| {
+        filter.replace_all(&acc, REDACTED).into_owned()
+    });
+
+    MessageBuilder::new().push_safe(redacted).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::sanitize;
+
+    #[test]
+    fn sanitize_without_filters() {
+        assert_eq!(sanitize("hello there", &[]), "hello there");
+        assert_eq!(sanitize("@everyone look", &[]), "@\u{200B}everyone look");
+        assert_eq!(sanitize("@here", &[]), "@\u{200B}here");
+    }
+
+    #[test]
+    fn sanitize_with_filters() {
+        let filters = [
+            Regex::new(r"https?://\S+").unwrap(),
+            Regex::new("(?i)badword").unwrap(),
+        ];
+        assert_eq!(
+            sanitize("see https://example.com and BadWord", &filters),
+            "see [redacted] and [redacted]"
+        );
+        assert_eq!(
+            sanitize("@everyone https://spam.example", &filters),
+            "@\u{200B}everyone [redacted]"
+        );
+    }
+}
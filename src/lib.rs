@@ -1,542 +1,5361 @@
+mod attachments;
+mod audit;
+mod batch;
+mod categories;
+mod codenames;
+pub mod commands;
+mod confirm;
+mod custom_id;
 mod database;
+mod dm_commands;
+mod duration;
+mod edits;
+mod embed;
 mod error;
+mod filter;
+mod office_hours;
+mod pagination;
+mod ratelimit;
+mod reactions;
+mod template;
+mod transcript;
 
-use std::{num::ParseIntError, result::Result as StdResult};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use attachments::{Download, Policy};
+use audit::Event;
+use batch::{Batcher, Poll, Push};
+use categories::{Choice, UNCATEGORIZED_LABEL};
+use chrono::{TimeZone, Utc};
+use confirm::Confirmation;
+use database::{
+    aliases::Alias,
+    backup::Backup,
+    blocks::Block,
+    config::{
+        self, AllowedExtensions, Analytics, AuditChannel, AuditEvents, BlockEmoji,
+        BlockStripsRoles, BlockedExtensions, Blockrole, Categories, CloseDm, CloseEmoji,
+        CoalesceWindow, CodenameCommand, CommandPermissions, Config, EditDebounce, ForwardStyle,
+        Guild, IgnoreBots, Inbox, MaintenanceInterval, MaintenanceMessage, MaintenanceMode,
+        MaxOpenThreads, MessageStyle, OfficeHours, OutOfHoursHold, OutOfHoursMessage,
+        OverflowInbox, ReopenSummary, ReplyPingRole, RevealUsername, ShowFooter, ShowThreadCount,
+        SlaMinutes, ThreadCreateRetries, TranscriptLimit, TranscriptRetention,
+    },
+    dead_letters::{DeadLetter, Direction},
+    erasure::Erasure,
+    filters::Filter,
+    maintenance::Maintenance,
+    messages::MessageLink,
+    notification_optouts::NotificationOptout,
+    queued_messages::QueuedMessage,
+    reminders::Reminder,
+    rooms::{Pause, Room},
+    scheduled_messages::ScheduledMessage,
+    snippets::Snippet,
+    stats::{self, Report},
+    transcripts::Transcript,
+};
+use edits::EditDebouncer;
 use error::{Error, Result};
+use office_hours::Schedule;
+use pagination::Page;
+use ratelimit::DmFailure;
+use reactions::Debouncer;
+use regex::Regex;
 use serenity::{
     async_trait,
+    builder::{CreateApplicationCommands, CreateEmbed},
     client::{Context, EventHandler},
+    http::Http,
     model::{
-        channel::{ChannelType, Message, PartialChannel, PartialGuildChannel},
-        gateway::Ready,
-        guild::Role,
-        id::{ChannelId, GuildId, RoleId, UserId},
+        channel::{
+            AttachmentType, ChannelType, GuildChannel, Message, PartialChannel,
+            PartialGuildChannel, Reaction,
+        },
+        event::MessageUpdateEvent,
+        gateway::{Activity, Ready},
+        guild::{Member, Role},
+        id::{ChannelId, GuildId, MessageId, RoleId, UserId},
         interactions::{
             application_command::{
-                ApplicationCommandInteraction,
+                ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
                 ApplicationCommandInteractionDataOptionValue as OptionValue,
-                ApplicationCommandOptionType, ApplicationCommandType,
             },
-            Interaction,
+            message_component::MessageComponentInteraction,
+            Interaction, InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
         },
+        permissions::Permissions,
+        user::User,
     },
     prelude::Mentionable,
     utils::{Color, MessageBuilder},
 };
-use sqlx::{FromRow, SqlitePool};
+use sqlx::SqlitePool;
+use tokio::sync::Notify;
+
+/// Delay between each user messaged by `/broadcast`, to stay clear of Discord's rate limits.
+const BROADCAST_DELAY: Duration = Duration::from_millis(500);
+
+/// Delay between each member unblocked by `/unblock-all`, for the same reason.
+const UNBLOCK_DELAY: Duration = Duration::from_millis(500);
+
+/// How many members are fetched at once by `/unblock-all`, the most Discord allows.
+const MEMBER_PAGE_SIZE: u64 = 1000;
+
+/// How many members `/unblock-all` unblocks between progress updates.
+const UNBLOCK_PROGRESS_INTERVAL: usize = 100;
+
+/// How many users `/broadcast` messages between progress updates.
+const BROADCAST_PROGRESS_INTERVAL: usize = 100;
+
+/// The shortest time between updates of the bot's status by `/presence`, as Discord only
+/// allows a few per minute.
+const PRESENCE_DEBOUNCE: Duration = Duration::from_secs(30);
+
+/// How often creating a thread is retried when rate limited, unless set with
+/// `thread_create_retries`.
+const DEFAULT_THREAD_CREATE_RETRIES: u64 = 2;
+pub(crate) const THREAD_CREATE_RETRIES_MAX: u64 = 5;
+
+/// How long to wait before retrying if Discord doesn't say.
+const THREAD_CREATE_RETRY_FALLBACK: Duration = Duration::from_secs(1);
+
+/// The longest wait for a retry worth holding the user's message for.
+const THREAD_CREATE_RETRY_MAX: Duration = Duration::from_secs(10);
+
+/// Looks up the resolved value of an option by name, for options that may be omitted.
+fn option<'a>(
+    options: &'a [ApplicationCommandInteractionDataOption],
+    name: &str,
+) -> Option<&'a OptionValue> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| opt.resolved.as_ref())
+}
+
+/// Signs off the bot's embeds, unless turned off with `/footer`.
+const FOOTER: &str = "With \u{2764} from the post office.";
+
+/// Permissions the bot needs in the inbox to create threads and forward messages into them.
+const INBOX_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::EMBED_LINKS)
+    .union(Permissions::ATTACH_FILES)
+    .union(Permissions::CREATE_PUBLIC_THREADS)
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::MANAGE_THREADS);
+
+/// How long messages that failed to forward are kept around.
+const DEAD_LETTER_RETENTION_DAYS: i64 = 30;
+
+/// How many dead letters `/deadletters` shows at once.
+const DEAD_LETTER_LIST_LIMIT: i64 = 10;
+
+/// How long a thread must be quiet before a user's reply pings the `/mentions` role.
+const REPLY_PING_COOLDOWN_MINUTES: i64 = 10;
+
+/// The most staff mentioned one by one instead of the `/mentions` role, well within Discord's
+/// limits on mentions and message length.
+const PING_USERS_MAX: usize = 25;
+
+/// How often due reminders are checked for.
+const REMINDER_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The longest wait `sla_minutes` can set before unanswered threads are alerted, a month.
+pub(crate) const SLA_MINUTES_MAX: u64 = 60 * 24 * 30;
+
+/// How many hours pass between maintenance runs, unless set with `/dbmaintenance interval`.
+const DEFAULT_MAINTENANCE_INTERVAL_HOURS: u64 = 24;
+pub(crate) const MAINTENANCE_INTERVAL_MAX: u64 = 24 * 30;
+
+/// The longest `/dbmaintenance transcripts` can keep transcripts for, about a century.
+pub(crate) const TRANSCRIPT_RETENTION_MAX_DAYS: u64 = 36_500;
+
+/// Names of the levels `/priority` can set, from least to most urgent.
+pub(crate) const PRIORITIES: [&str; 3] = ["normal", "high", "urgent"];
+
+/// Prefix of the paged queries behind `/history`, followed by the user's ID.
+const HISTORY_QUERY: &str = "history-";
+
+/// Checks that the user may page through the results of `query`, e.g. `/list`.
+fn require_list_permission(query: &str, perms: Permissions) -> Result<()> {
+    match query {
+        "list" | "snoozed" if !perms.manage_channels() => Err(Error::User(
+            "You don't have `Manage Channels` permission.".into(),
+        )),
+        "blocklist" if !perms.manage_roles() => Err(Error::User(
+            "You don't have `Manage Roles` permission.".into(),
+        )),
+        "snippets" if !perms.manage_channels() => Err(Error::User(
+            "You don't have `Manage Channels` permission.".into(),
+        )),
+        _ if query.starts_with(HISTORY_QUERY) && !perms.manage_channels() => Err(Error::User(
+            "You don't have `Manage Channels` permission.".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The command whose results a paged `query` shows, for permission overrides.
+fn list_command(query: &str) -> &str {
+    match query {
+        "snippets" => "snippet",
+        "snoozed" => "list",
+        _ if query.starts_with(HISTORY_QUERY) => "history",
+        _ => query,
+    }
+}
+
+/// Formats a message in a room's thread for its transcript. The bot's own messages are mostly
+/// forwarded from the user, so they're attributed to the room's codename, except for command
+/// responses, e.g. to `/reply`, which are attributed to whoever used the command.
+fn transcript_line(msg: &Message, bot: UserId, codename: &str) -> transcript::Line {
+    let author = match &msg.interaction {
+        Some(interaction) => interaction.user.tag(),
+        None if msg.author.id == bot => codename.to_owned(),
+        None => msg.author.tag(),
+    };
+
+    let content = std::iter::once(msg.content.as_str())
+        .chain(msg.embeds.iter().filter_map(|e| e.description.as_deref()))
+        .chain(msg.attachments.iter().map(|a| a.url.as_str()))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    transcript::Line {
+        at: Utc
+            .timestamp_opt(msg.timestamp.unix_timestamp(), 0)
+            .unwrap(),
+        author,
+        content,
+    }
+}
+
+/// Marks threads with a raised priority, both in `/list` and in their thread name.
+fn priority_prefix(priority: i64) -> &'static str {
+    if priority > 0 {
+        "\u{1f534} "
+    } else {
+        ""
+    }
+}
+
+/// Marks threads where forwarding to the user is paused, in their thread name.
+const PAUSE_PREFIX: &str = "\u{23f8}\u{fe0f} ";
+
+/// Splits the mentions and pause tag `forward_to_thread` puts before a user's message off its
+/// forwarded copy.
+fn split_forward_prefix(content: &str) -> (&str, &str) {
+    let mut rest = content;
+    while let Some((mention, tail)) = rest
+        .strip_prefix("<@")
+        .and_then(|tail| tail.split_once("> "))
+    {
+        let id = mention.strip_prefix('&').unwrap_or(mention);
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+        rest = tail;
+    }
+    let rest = rest.strip_prefix(PAUSE_PREFIX).unwrap_or(rest);
+    content.split_at(content.len() - rest.len())
+}
+
+/// Names a room's thread after its codename, with prefixes for its state.
+fn thread_name(room: &Room) -> String {
+    format!(
+        "{}{}{}",
+        if room.pause.is_some() {
+            PAUSE_PREFIX
+        } else {
+            ""
+        },
+        priority_prefix(room.priority),
+        &room.codename
+    )
+}
+
+/// Appends the user's name to a thread name, for servers that turned on `/revealusername`, and
+/// keeps the result within Discord's limit.
+fn with_username(name: String, username: Option<&str>) -> String {
+    let name = match username {
+        Some(username) => format!("{} ({})", name, username),
+        None => name,
+    };
+    codenames::truncate(&name, codenames::THREAD_NAME_MAX_LEN).to_owned()
+}
+
+/// How many of a thread's most recent messages `/close` saves by default.
+const DEFAULT_TRANSCRIPT_LIMIT: usize = 1000;
+pub(crate) const TRANSCRIPT_LIMIT_MAX: usize = 10_000;
+
+/// How many of a thread's most recent messages `/merge` carries over for context.
+const MERGE_CONTEXT_LIMIT: usize = 50;
+
+/// Bounds on how long `/coalesce` may hold messages.
+pub(crate) const COALESCE_WINDOW_MIN_MS: u64 = 100;
+pub(crate) const COALESCE_WINDOW_MAX_MS: u64 = 10_000;
+
+/// How long edits of a forwarded message are collapsed into one, unless set with
+/// `edit_debounce`, and the longest that's allowed.
+const DEFAULT_EDIT_DEBOUNCE_MS: u64 = 1500;
+pub(crate) const EDIT_DEBOUNCE_MAX_MS: u64 = 10_000;
+
+const DEFAULT_CODENAME_COMMAND: &str = "!codename";
+
+/// DM command that tells users their codename and whether they're blocked.
+const WHOAMI_COMMAND: &str = "!whoami";
+
+/// DM command for opening a room in another category, while `categories` are set up.
+const NEW_THREAD_COMMAND: &str = "!new";
+
+const CATEGORY_PROMPT: &str = "What is your message about?";
+
+const ROOM_PROMPT: &str = "Which of your threads should this go to? Reply to a message in a \
+    thread to skip this, or start your message with `!new` to open another thread.";
+
+const INBOX_FULL_MESSAGE: &str =
+    "Staff are handling a lot of threads right now, please try again later.";
+
+/// Forwarded in place of staff messages whose text Discord withheld, see
+/// [`Bot::with_message_content`].
+const CONTENT_UNAVAILABLE: &str = "[message content unavailable]";
+
+/// Forwarded in place of DMs with nothing serenity can read, which is mostly polls.
+const UNSUPPORTED_MESSAGE: &str = "[unsupported message, e.g. a poll]";
+
+/// The text of a user's DM, or a placeholder if there's nothing else to forward either.
+fn dm_content(msg: &Message) -> &str {
+    if msg.content.is_empty() && msg.attachments.is_empty() && msg.sticker_items.is_empty() {
+        return UNSUPPORTED_MESSAGE;
+    }
+    &msg.content
+}
+
+/// Sent to users whose first message couldn't get a thread, even after retrying.
+const THREAD_BUSY_MESSAGE: &str =
+    "Sorry, staff are busy right now and your message didn't get through. Please resend it in a moment.";
+
+const BLOCKED_MESSAGE: &str = "You have been blocked by a server admin.";
+
+/// Appended to forwarded messages whose original was edited, as the copy is only edited by the
+/// bot.
+const EDITED_MARKER: &str = "*(edited)*";
+
+fn with_edited_marker(content: &str) -> String {
+    format!("{} {}", content, EDITED_MARKER)
+}
+
+/// Posted in a user's thread when they leave the server, as they may no longer accept DMs.
+const MEMBER_LEFT_MESSAGE: &str =
+    "\u{26a0}\u{fe0f} This user has left the server; replies may not be deliverable.";
+
+fn greeting(codename: &str) -> String {
+    format!("You've been assigned the codename `{}`.", codename)
+}
+
+/// Placeholder values `/preview` renders templates with.
+const PREVIEW_CODENAME: &str = "peaceful bonefish";
+const PREVIEW_REASON: &str = "Resolved.";
+
+const DEFAULT_MAINTENANCE_MESSAGE: &str =
+    "Modmail isn't taking new messages right now, please try again later.";
+
+const DEFAULT_OUT_OF_HOURS_MESSAGE: &str =
+    "Staff are currently outside of office hours, so replies may take a while.";
+
+/// What a successfully executed command replies with.
+struct Response {
+    content: String,
+    /// A file sent alongside the reply, as its contents and filename.
+    attachment: Option<(Vec<u8>, String)>,
+    /// The page shown, if the reply is part of a longer list, and how many pages there are.
+    page: Option<(Page, usize)>,
+    /// An action the reply asks to confirm with a button.
+    confirm: Option<Confirmation>,
+}
+
+impl From<String> for Response {
+    fn from(content: String) -> Self {
+        Self {
+            content,
+            attachment: None,
+            page: None,
+            confirm: None,
+        }
+    }
+}
+
+impl From<&str> for Response {
+    fn from(content: &str) -> Self {
+        content.to_owned().into()
+    }
+}
 
 pub struct Bot {
-    guild: GuildId,
+    /// The guild the bot serves, which `/rebind-guild` can change at runtime.
+    guild: AtomicU64,
+    owner: UserId,
     pool: SqlitePool,
+    config: Config,
+    /// The slash commands registered with Discord.
+    commands: CreateApplicationCommands,
+    /// Commands whose responses only the user of the command can see.
+    ephemeral: HashSet<String>,
+    /// Whether the bot has the message content intent, without which staff messages arrive
+    /// without their text.
+    message_content: bool,
+    /// Messages held back by `/coalesce`.
+    batcher: Batcher,
+    /// Reactions on forwarded messages that were reported to staff recently.
+    reactions: Debouncer,
+    /// Edits of forwarded messages waiting for the editing to stop.
+    edits: EditDebouncer,
+    /// Whether commands were registered already, since `ready` fires again on reconnect.
+    registered: AtomicBool,
+    /// Whether the reminder and scheduled message tasks were started already, for the same
+    /// reason.
+    reminding: AtomicBool,
+    /// Whether the maintenance task was started already.
+    maintaining: AtomicBool,
+    /// Whether `/unblock-all` is running, so confirming it again doesn't start it twice.
+    unblocking: Arc<AtomicBool>,
+    /// Whether `/broadcast` is running, so two broadcasts don't message users at once.
+    broadcasting: Arc<AtomicBool>,
+    /// Whether the inbox's permissions were checked already, so reconnecting doesn't repeat
+    /// the warning.
+    inbox_checked: AtomicBool,
+    /// Notified when the number of open threads changes, for `/presence`.
+    presence: Arc<Notify>,
+    /// Whether the presence task was started already.
+    presenting: AtomicBool,
+    /// Notified when the bot can't keep running, see [`Bot::shutdown_signal`].
+    shutdown: Arc<Notify>,
 }
 
 impl Bot {
-    pub fn new<T>(pool: SqlitePool, guild: T) -> Self
+    pub fn new<T, U>(pool: SqlitePool, guild: T, owner: U) -> Self
     where
         T: Into<GuildId>,
+        U: Into<UserId>,
     {
         Self {
+            config: Config::new(pool.clone()),
             pool,
-            guild: guild.into(),
+            guild: AtomicU64::new(guild.into().0),
+            owner: owner.into(),
+            commands: commands::default_commands(),
+            ephemeral: commands::EPHEMERAL
+                .iter()
+                .map(|&name| name.to_owned())
+                .collect(),
+            message_content: true,
+            batcher: Batcher::default(),
+            reactions: Debouncer::default(),
+            edits: EditDebouncer::default(),
+            registered: AtomicBool::new(false),
+            reminding: AtomicBool::new(false),
+            maintaining: AtomicBool::new(false),
+            unblocking: Arc::new(AtomicBool::new(false)),
+            broadcasting: Arc::new(AtomicBool::new(false)),
+            inbox_checked: AtomicBool::new(false),
+            presence: Arc::new(Notify::new()),
+            presenting: AtomicBool::new(false),
+            shutdown: Arc::new(Notify::new()),
         }
     }
 
-    async fn config(&self, key: &str) -> Result<Option<String>> {
-        Ok(sqlx::query!("SELECT value FROM config WHERE key = ?", key)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(anyhow::Error::from)?
-            .map(|r| r.value))
+    /// Notified when the bot hits an error it can't recover from, e.g. because it isn't a
+    /// member of its guild. Whoever runs the client should shut it down then.
+    pub fn shutdown_signal(&self) -> Arc<Notify> {
+        self.shutdown.clone()
+    }
+
+    fn guild(&self) -> GuildId {
+        GuildId(self.guild.load(Ordering::SeqCst))
+    }
+
+    /// Replaces the slash commands registered by the bot, which default to
+    /// [`commands::default_commands`]. Commands the bot doesn't implement are answered with an
+    /// error, so this is mostly useful for leaving out or adjusting the defaults.
+    pub fn with_commands(mut self, commands: CreateApplicationCommands) -> Self {
+        self.commands = commands;
+        self
     }
 
-    async fn set_config(&self, key: &str, value: &str) -> Result<()> {
-        let res = sqlx::query!(
-            "INSERT INTO config (key, value) VALUES (?, ?)
-            ON CONFLICT (key) DO UPDATE SET value = excluded.value",
-            key,
-            value
+    /// Chooses whether responses to the command called `name` are only shown to whoever used
+    /// it. By default, that's the case for the commands in [`commands::EPHEMERAL`]. Errors are
+    /// always ephemeral.
+    pub fn with_ephemeral(mut self, name: &str, ephemeral: bool) -> Self {
+        if ephemeral {
+            self.ephemeral.insert(name.to_owned());
+        } else {
+            self.ephemeral.remove(name);
+        }
+        self
+    }
+
+    /// Tells the bot whether it was started with the message content intent. Without it, staff
+    /// messages are forwarded with a placeholder instead of their text, and edits aren't
+    /// mirrored. DMs are unaffected, as Discord always includes their content.
+    pub fn with_message_content(mut self, available: bool) -> Self {
+        self.message_content = available;
+        self
+    }
+
+    /// The text of a message in a thread, or a placeholder if Discord withheld it.
+    fn thread_content(&self, msg: &Message) -> String {
+        if !self.message_content && msg.content.is_empty() {
+            return CONTENT_UNAVAILABLE.to_owned();
+        }
+        MessageBuilder::new().push_safe(&msg.content).build()
+    }
+
+    async fn filters(&self) -> Result<Vec<Regex>> {
+        Ok(Filter::all(&self.pool)
+            .await?
+            .iter()
+            .map(|f| Regex::new(&f.pattern).expect("got malformed filter from database"))
+            .collect())
+    }
+
+    /// Downloads a message's attachments to forward them, leaving out file types that aren't
+    /// allowed and noting them in `content` instead.
+    async fn download_attachments(
+        &self,
+        msg: &Message,
+        content: &str,
+    ) -> Result<(String, Vec<Download>)> {
+        let policy = Policy {
+            allowed: self.config.get(AllowedExtensions).await?.map(|ext| ext.0),
+            blocked: self
+                .config
+                .get(BlockedExtensions)
+                .await?
+                .unwrap_or_default()
+                .0,
+        };
+        let (files, blocked) = attachments::download(&msg.attachments, &policy).await?;
+        Ok((attachments::with_notes(content, &blocked), files))
+    }
+
+    /// Records a message that failed to forward, so staff can follow up on it.
+    async fn record_undelivered<T>(
+        &self,
+        ctx: &Context,
+        res: serenity::Result<T>,
+        room: &Room,
+        direction: Direction,
+        content: &str,
+    ) -> Result<T> {
+        let err = match res {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        ratelimit::log_failure(&ctx.http, &err).await;
+        if direction == Direction::ToUser {
+            self.explain_dm_failure(ctx, room, &err).await;
+        }
+
+        let target = match direction {
+            Direction::ToThread => room.channel_id.0,
+            Direction::ToUser => room.user_id.0,
+        };
+        DeadLetter::new(
+            &self.pool,
+            Some(room.room_id),
+            direction,
+            target,
+            content.to_owned(),
+            err.to_string(),
         )
-        .execute(&self.pool)
-        .await
-        .map_err(anyhow::Error::from)?;
+        .await?;
+        DeadLetter::prune(
+            &self.pool,
+            chrono::Duration::days(DEAD_LETTER_RETENTION_DAYS),
+        )
+        .await?;
 
-        assert_eq!(res.rows_affected(), 1u64);
-        Ok(())
+        Err(anyhow::Error::from(err).into())
+    }
+
+    /// Tells staff in the room's thread why a message to the user failed, so they know whether
+    /// retrying with `/resend` can help.
+    async fn explain_dm_failure(&self, ctx: &Context, room: &Room, err: &serenity::Error) {
+        let explanation = match ratelimit::dm_failure(err) {
+            DmFailure::Undeliverable => match self.guild().member(ctx, room.user_id).await {
+                Ok(_) => "The user doesn't accept DMs from the bot, e.g. because they closed \
+                    their DMs or blocked it."
+                    .to_owned(),
+                Err(_) => "The user left the server, and Discord only delivers DMs to users \
+                    who share a server with the bot."
+                    .to_owned(),
+            },
+            DmFailure::RateLimited => {
+                "Discord is rate limiting the bot. Try `/resend` in a moment.".to_owned()
+            }
+            DmFailure::Other => format!("Discord rejected the message: {}", err),
+        };
+
+        let res = room
+            .channel_id
+            .say(
+                ctx,
+                format!(
+                    "\u{26a0}\u{fe0f} That message didn't reach the user. {}",
+                    explanation
+                ),
+            )
+            .await;
+        if let Err(err) = res {
+            tracing::warn!(source = ?err, codename = %room.codename, "Failed to explain undelivered message.");
+        }
     }
 
-    async fn unset_config(&self, key: &str) -> Result<()> {
-        sqlx::query!("DELETE FROM config WHERE key = ?", key)
-            .execute(&self.pool)
+    async fn register_commands(&self, ctx: &Context) -> Result<()> {
+        self.guild()
+            .set_application_commands(ctx, |cmds| {
+                *cmds = self.commands.clone();
+                cmds
+            })
             .await
             .map_err(anyhow::Error::from)?;
         Ok(())
     }
 
-    async fn get_blockrole(&self) -> Result<Option<RoleId>> {
-        let raw = self.config("blockrole").await?;
-        Ok(raw.map(|s| RoleId(s.parse().expect("got malformed ID from database"))))
+    /// Cleans up state that may have diverged while the bot was offline.
+    async fn reconcile(&self, ctx: &Context) -> Result<()> {
+        for room in Room::stale_duplicates(&self.pool).await? {
+            tracing::warn!(codename = %room.codename, user = %room.user_id, "Removing duplicate room.");
+            let _ = room
+                .channel_id
+                .edit_thread(ctx, |edit| edit.archived(true))
+                .await;
+            room.delete(&self.pool).await?;
+        }
+
+        self.presence.notify_one();
+        Ok(())
     }
 
-    async fn set_blockrole(&self, role: &Role) -> Result<()> {
-        let id = role.id.0.to_string();
-        self.set_config("blockrole", &id).await
+    /// Generates a codename no other room or user is using.
+    async fn fresh_codename(&self) -> Result<String> {
+        loop {
+            let candidate = codenames::sanitize(&petname::petname(2, " "));
+            if !Room::codename_exists(&self.pool, &candidate).await?
+                && Alias::owner(&self.pool, &candidate).await?.is_none()
+            {
+                return Ok(candidate);
+            }
+        }
     }
 
-    async fn unset_blockrole(&self) -> Result<()> {
-        self.unset_config("blockrole").await
+    /// Whether `codename` belongs to someone other than `user`, either as their open room's or
+    /// as a codename given with `/set-codename`.
+    async fn codename_taken(&self, codename: &str, user: UserId) -> Result<bool> {
+        if let Some(room) = Room::get_by_codename(&self.pool, codename).await? {
+            if room.user_id != user {
+                return Ok(true);
+            }
+        }
+        Ok(matches!(Alias::owner(&self.pool, codename).await?, Some(owner) if owner != user))
     }
 
-    async fn get_inbox(&self) -> Result<Option<ChannelId>> {
-        let raw = self.config("inbox").await?;
-        Ok(raw.map(|s| ChannelId(s.parse().expect("got malformed ID from database"))))
+    async fn room_from_codename(&self, codename: &str) -> Result<Room> {
+        Room::get_by_codename(&self.pool, codename)
+            .await
+            .and_then(|opt| {
+                opt.ok_or_else(|| {
+                    Error::User(format!("No thread with codename `{}` found.", codename))
+                })
+            })
     }
 
-    async fn set_inbox(&self, channel: &PartialChannel) -> Result<()> {
-        let id = channel.id.0.to_string();
-        self.set_config("inbox", &id).await
+    /// Entries of a list that can be paged through, such as `/list`.
+    async fn list_entries(&self, query: &str) -> Result<Vec<String>> {
+        match query {
+            "list" | "snoozed" => {
+                let now = Utc::now();
+                Ok(Room::all(&self.pool)
+                    .await?
+                    .iter()
+                    .filter(|room| room.snoozed(now) == (query == "snoozed"))
+                    .map(|room| {
+                        format!(
+                            "{}`{}` {}, active <t:{}:R>{}{}{}",
+                            priority_prefix(room.priority),
+                            &room.codename,
+                            room.channel_id.mention(),
+                            room.last_activity_at.timestamp(),
+                            match room.claimed_by {
+                                Some(staff) => format!(", claimed by {}", staff.mention()),
+                                None => String::new(),
+                            },
+                            match room.snoozed_until {
+                                Some(until) if until > now => {
+                                    format!(", snoozed until <t:{}:f>", until.timestamp())
+                                }
+                                _ => String::new(),
+                            },
+                            if room.archived { " (archived)" } else { "" }
+                        )
+                    })
+                    .collect())
+            }
+
+            "blocklist" => Ok(Block::all(&self.pool)
+                .await?
+                .iter()
+                .map(|user| format!("{} (`{}`)", user.mention(), user))
+                .collect()),
+
+            "snippets" => Ok(Snippet::all(&self.pool)
+                .await?
+                .iter()
+                .map(|snippet| match &snippet.emoji {
+                    Some(emoji) => format!("`{}` ({}): {}", snippet.name, emoji, snippet.content),
+                    None => format!("`{}`: {}", snippet.name, snippet.content),
+                })
+                .collect()),
+
+            _ => match query
+                .strip_prefix(HISTORY_QUERY)
+                .and_then(|user| user.parse::<u64>().ok())
+            {
+                Some(user) => Ok(Transcript::for_user(&self.pool, user.into())
+                    .await?
+                    .iter()
+                    .map(|transcript| {
+                        format!(
+                            "`#{}` `{}`, closed <t:{}:f>",
+                            transcript.transcript_id,
+                            &transcript.codename,
+                            transcript.closed_at.timestamp()
+                        )
+                    })
+                    .collect()),
+                None => Err(Error::UnknownCommand(query.to_owned())),
+            },
+        }
     }
 
-    async fn unset_inbox(&self) -> Result<()> {
-        self.unset_config("inbox").await
+    async fn show_page(&self, mut page: Page) -> Result<Response> {
+        let entries = self.list_entries(&page.query).await?;
+        if entries.is_empty() {
+            return Ok(match page.query.as_str() {
+                "list" => "There are no open threads.",
+                "snoozed" => "There are no snoozed threads.",
+                "snippets" => "There are no snippets.",
+                query if query.starts_with(HISTORY_QUERY) => "This user has no closed threads.",
+                _ => "Nobody is blocked.",
+            }
+            .into());
+        }
+
+        let (content, pages) = page.render(&entries);
+        Ok(Response {
+            content,
+            attachment: None,
+            page: (pages > 1).then_some((page, pages)),
+            confirm: None,
+        })
     }
 
-    async fn room_from_codename(&self, codename: &str) -> Result<Option<Room>> {
-        Ok(
-            sqlx::query_as!(RawRoom, "SELECT * FROM rooms WHERE codename = ?", codename)
-                .fetch_optional(&self.pool)
-                .await
-                .map_err(anyhow::Error::from)?
-                .map(|rt| Room::try_from(rt).expect("got malformed Room object from database")),
+    /// Carries out an action once whoever asked for it pressed its confirmation button.
+    async fn confirm(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        confirmation: Confirmation,
+    ) -> Result<Response> {
+        if confirmation.user != component.user.id {
+            return Err(Error::User(
+                "Only whoever used the command can confirm it.".into(),
+            ));
+        }
+        if confirmation.expired(Utc::now()) {
+            return Err(Error::User(
+                "This button has expired, please use the command again.".into(),
+            ));
+        }
+
+        let (perms, by_role) = self
+            .command_permissions(&confirmation.action, component.member.as_ref().unwrap())
+            .await?;
+        match confirmation.action.as_str() {
+            "unblock-all" => {
+                if !by_role && !perms.manage_roles() {
+                    return Err(Error::User(
+                        "You don't have `Manage Roles` permission.".into(),
+                    ));
+                }
+                self.unblock_all(ctx, component.channel_id).await
+            }
+            "close" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+                let thread = match confirmation.target {
+                    Some(thread) => ChannelId(thread),
+                    None => return Err(Error::UnknownCommand(component.data.custom_id.clone())),
+                };
+
+                // the room is gone already, so `thread_delete` has nothing left to clean up
+                thread.delete(ctx).await.map_err(anyhow::Error::from)?;
+                Ok("Deleted the thread.".into())
+            }
+            _ => Err(Error::UnknownCommand(component.data.custom_id.clone())),
+        }
+    }
+
+    /// Starts taking the block role from every member who has it, see [`unblock_members`].
+    async fn unblock_all(&self, ctx: &Context, channel: ChannelId) -> Result<Response> {
+        let role = self.config.get(Blockrole).await.and_then(|opt| {
+            opt.ok_or_else(|| Error::User("There's no block role defined.".into()))
+        })?;
+        // the button stays usable until the response replaces it
+        if self.unblocking.swap(true, Ordering::SeqCst) {
+            return Err(Error::User("Members are already being unblocked.".into()));
+        }
+
+        let blocked = match self.blocked_members(ctx, role).await {
+            Ok(blocked) => blocked,
+            Err(err) => {
+                self.unblocking.store(false, Ordering::SeqCst);
+                return Err(err);
+            }
+        };
+        let count = blocked.len();
+        tokio::spawn(unblock_members(
+            self.pool.clone(),
+            ctx.http.clone(),
+            role,
+            blocked,
+            channel,
+            self.unblocking.clone(),
+        ));
+        Ok(format!(
+            "Unblocking {} member(s). This can take a while, so progress is posted in this channel.",
+            count
         )
+        .into())
+    }
+
+    /// The members who have the block `role`.
+    async fn blocked_members(&self, ctx: &Context, role: RoleId) -> Result<Vec<Member>> {
+        let mut blocked = Vec::new();
+        let mut after = None;
+        loop {
+            let members = self
+                .guild()
+                .members(ctx, Some(MEMBER_PAGE_SIZE), after)
+                .await
+                .map_err(anyhow::Error::from)?;
+            after = members.last().map(|member| member.user.id);
+            let full = members.len() as u64 == MEMBER_PAGE_SIZE;
+            blocked.extend(
+                members
+                    .into_iter()
+                    .filter(|member| member.roles.contains(&role)),
+            );
+            if !full {
+                break;
+            }
+        }
+        Ok(blocked)
+    }
+
+    /// Handles the buttons on paged lists, confirmations and category prompts.
+    async fn execute_component(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+    ) -> Result<Response> {
+        if let Some(choice) = Choice::parse(&component.data.custom_id) {
+            return self.choose_category(ctx, component, choice).await;
+        }
+        if let Some(confirmation) = Confirmation::parse(&component.data.custom_id) {
+            return self.confirm(ctx, component, confirmation).await;
+        }
+
+        let page = Page::parse(&component.data.custom_id)
+            .ok_or_else(|| Error::UnknownCommand(component.data.custom_id.clone()))?;
+        if page.expired(Utc::now()) {
+            return Err(Error::User(
+                "These buttons have expired, please use the command again.".into(),
+            ));
+        }
+
+        let (perms, by_role) = self
+            .command_permissions(
+                list_command(&page.query),
+                component.member.as_ref().unwrap(),
+            )
+            .await?;
+        if !by_role {
+            require_list_permission(&page.query, perms)?;
+        }
+        self.show_page(page).await
+    }
+
+    /// Resolves the room from a command's `codename` option, defaulting to the thread the
+    /// command was used in.
+    async fn room_from_command(&self, cmd: &ApplicationCommandInteraction) -> Result<Room> {
+        self.room_from_options(&cmd.data.options, cmd.channel_id)
+            .await
+    }
+
+    /// Like [`Bot::room_from_command`], for the options of a subcommand.
+    async fn room_from_options(
+        &self,
+        options: &[ApplicationCommandInteractionDataOption],
+        channel: ChannelId,
+    ) -> Result<Room> {
+        match option(options, "codename") {
+            Some(OptionValue::String(codename)) => self.room_from_codename(codename).await,
+            _ => Room::get_by_channel(&self.pool, channel)
+                .await?
+                .ok_or_else(|| {
+                    Error::User("Use this command in a thread or give a codename.".into())
+                }),
+        }
+    }
+
+    /// Explains why the bot can't give members `role`, if it can't. Discord only lets it assign
+    /// roles below its own highest one.
+    async fn unassignable(&self, ctx: &Context, role: &Role) -> Result<Option<&'static str>> {
+        if role.id.0 == self.guild().0 {
+            return Ok(Some("everyone already has it."));
+        }
+        if role.managed {
+            return Ok(Some("it's managed by an integration."));
+        }
+
+        let guild = self
+            .guild()
+            .to_partial_guild(ctx)
+            .await
+            .map_err(anyhow::Error::from)?;
+        let member = self
+            .guild()
+            .member(ctx, ctx.cache.current_user_id())
+            .await
+            .map_err(anyhow::Error::from)?;
+        let highest = member
+            .roles
+            .iter()
+            .filter_map(|id| guild.roles.get(id))
+            .map(|role| role.position)
+            .max()
+            .unwrap_or(0);
+
+        if role.position >= highest {
+            Ok(Some(
+                "it's not below my highest role. Move my role above it in the server settings.",
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Checks that `channel` can be used as the inbox.
+    async fn check_inbox_channel(&self, ctx: &Context, channel: &PartialChannel) -> Result<()> {
+        if !matches!(channel.kind, ChannelType::Text | ChannelType::News) {
+            return Err(Error::User(
+                "The inbox must be a text or announcement channel.".into(),
+            ));
+        }
+
+        let missing = self.missing_inbox_permissions(ctx, channel.id).await?;
+        if !missing.is_empty() {
+            return Err(Error::User(format!(
+                "I'm missing these permissions in {}: {}.",
+                channel.id.mention(),
+                missing
+            )));
+        }
+        Ok(())
+    }
+
+    /// Recreates the room's thread in `inbox` and archives the old one, which is left with a
+    /// link to the new thread.
+    async fn move_room(&self, ctx: &Context, room: &mut Room, inbox: ChannelId) -> Result<()> {
+        let old = room.channel_id;
+        let inbox_msg = inbox
+            .send_message(ctx, |f| {
+                f.content(format!(
+                    "Thread `{}` moved here from {}.",
+                    &room.codename,
+                    old.mention()
+                ))
+            })
+            .await
+            .map_err(anyhow::Error::from)?;
+        let name = self.thread_title(ctx, room).await?;
+        let thread = inbox
+            .create_public_thread(ctx, inbox_msg.id, |thread| thread.name(name))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        room.update_channel(&self.pool, thread.id).await?;
+        room.set_inbox_id(&self.pool, inbox).await?;
+        if room.archived {
+            room.set_archived(&self.pool, false).await?;
+        }
+
+        let res = async {
+            old.say(
+                ctx,
+                format!("This thread moved to {}.", thread.id.mention()),
+            )
+            .await?;
+            old.edit_thread(ctx, |edit| edit.archived(true)).await
+        }
+        .await;
+        if let Err(err) = res {
+            tracing::warn!(source = ?err, codename = %room.codename, "Failed to archive old thread.");
+        }
+        Ok(())
+    }
+
+    /// Returns the permissions the bot is missing in `inbox`.
+    async fn missing_inbox_permissions(
+        &self,
+        ctx: &Context,
+        inbox: ChannelId,
+    ) -> Result<Permissions> {
+        let perms = self
+            .permissions_in(ctx, inbox, ctx.cache.current_user_id())
+            .await?;
+        Ok(INBOX_PERMISSIONS - perms)
+    }
+
+    /// The permissions `user` has in `channel`, which must belong to the guild, e.g. not a
+    /// stray ID from `/import`.
+    async fn permissions_in(
+        &self,
+        ctx: &Context,
+        channel: ChannelId,
+        user: UserId,
+    ) -> Result<Permissions> {
+        let guild = self
+            .guild()
+            .to_partial_guild(ctx)
+            .await
+            .map_err(anyhow::Error::from)?;
+        let member = self
+            .guild()
+            .member(ctx, user)
+            .await
+            .map_err(anyhow::Error::from)?;
+        let channel = channel
+            .to_channel(ctx)
+            .await
+            .map_err(anyhow::Error::from)?
+            .guild()
+            .ok_or_else(|| {
+                Error::User(format!(
+                    "{} isn't a channel in this server.",
+                    channel.mention()
+                ))
+            })?;
+
+        Ok(guild
+            .user_permissions_in(&channel, &member)
+            .map_err(anyhow::Error::from)?)
     }
 
-    async fn room_from_channel(&self, channel_id: u64) -> Result<Option<Room>> {
-        // HACK: query!() drops temporaries for some reason, must pass reference
-        let temp = &channel_id.to_string();
-        Ok(
-            sqlx::query_as!(RawRoom, "SELECT * FROM rooms WHERE channel_id = ?", temp)
-                .fetch_optional(&self.pool)
-                .await
-                .map_err(anyhow::Error::from)?
-                .map(|rt| Room::try_from(rt).expect("got malformed Room object from database")),
-        )
-    }
+    /// Blocks the user and gives them the block role, removing the roles configured with
+    /// `/blockstrip`.
+    async fn block_user(&self, ctx: &Context, user: UserId) -> Result<()> {
+        let role = self.config.get(Blockrole).await.and_then(|opt| {
+            opt.ok_or_else(|| Error::User("There's no block role defined.".into()))
+        })?;
+
+        let member = match self.guild().member(ctx, user).await {
+            Ok(member) => Some(member),
+            // users who left get the role when they rejoin, see `guild_member_addition`
+            Err(err) if ratelimit::unknown_member(&err) => None,
+            Err(err) => return Err(anyhow::Error::from(err).into()),
+        };
+
+        Block::add(&self.pool, user).await?;
+        if let Some(mut member) = member {
+            member.add_role(ctx, role).await.map_err(|_| {
+                Error::User("Missing permissions or configured block role is invalid.".into())
+            })?;
+
+            let strip: Vec<_> = self
+                .config
+                .get(BlockStripsRoles)
+                .await?
+                .unwrap_or_default()
+                .0
+                .into_iter()
+                .filter(|id| *id != role && member.roles.contains(id))
+                .collect();
+            if !strip.is_empty() {
+                // recorded first, so `/unblock` can give back any roles that do get removed
+                Block::add_stripped_roles(&self.pool, user, &strip).await?;
+                member.remove_roles(ctx, &strip).await.map_err(|_| {
+                    Error::User(
+                        "Blocked, but I'm missing permissions to remove their other roles.".into(),
+                    )
+                })?;
+            }
+        }
+
+        self.audit(ctx, Event::Block, format!("Blocked {}.", user.mention()))
+            .await;
+        Ok(())
+    }
+
+    /// Whether `user` is blocked, either with `/block` or by being given the block role by hand.
+    /// Only the former is known for users who left the server, whose roles can't be checked.
+    async fn blocked(&self, ctx: &Context, user: &User) -> Result<bool> {
+        if Block::exists(&self.pool, user.id).await? {
+            return Ok(true);
+        }
+
+        let role = match self.config.get(Blockrole).await? {
+            Some(role) => role,
+            None => return Ok(false),
+        };
+        match user.has_role(ctx, self.guild(), role).await {
+            Ok(has_role) => Ok(has_role),
+            Err(err) if ratelimit::unknown_member(&err) => Ok(false),
+            Err(err) => Err(anyhow::Error::from(err).into()),
+        }
+    }
+
+    /// Whether the bot's embeds get [`FOOTER`], see `/footer`.
+    async fn show_footer(&self) -> bool {
+        // a failed lookup shouldn't keep the response from going out
+        self.config
+            .get(ShowFooter)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(true)
+    }
+
+    /// Posts `content` in the audit channel, if there is one and `event` is among the
+    /// `audit_events` to record.
+    async fn audit(&self, ctx: &Context, event: Event, content: String) {
+        let res = async {
+            let channel = match self.config.get(AuditChannel).await? {
+                Some(channel) => channel,
+                None => return Ok(()),
+            };
+            let events = self.config.get(AuditEvents).await?.unwrap_or_default();
+            if !events.contains(event) {
+                return Ok(());
+            }
+
+            channel
+                .send_message(ctx, |msg| {
+                    msg.content(content)
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                })
+                .await
+                .map_err(anyhow::Error::from)?;
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        if let Err(err) = res {
+            tracing::warn!(source = ?err, %event, "Failed to post in the audit channel.");
+        }
+    }
+
+    /// Warns about missing permissions in the inbox, so they don't go unnoticed until a DM
+    /// fails to forward.
+    async fn check_inbox(&self, ctx: &Context) -> Result<()> {
+        let inbox = match self.config.get(Inbox).await? {
+            Some(inbox) => inbox,
+            None => return Ok(()),
+        };
+
+        let missing = self.missing_inbox_permissions(ctx, inbox).await?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        tracing::warn!(%inbox, %missing, "Missing permissions in inbox.");
+        if !missing.intersects(Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES) {
+            inbox
+                .say(
+                    ctx,
+                    format!(
+                        "I'm missing these permissions here, so some messages may not be forwarded: {}.",
+                        missing
+                    ),
+                )
+                .await
+                .map_err(anyhow::Error::from)?;
+        }
+
+        Ok(())
+    }
+
+    /// The permissions of `member` using `command`, and whether `/commandrole` gave the command
+    /// a role that they have. That role stands in for the permission the command requires, but
+    /// not for any other check, and members without it are turned away. Commands only the
+    /// owner can use stay that way.
+    async fn command_permissions(
+        &self,
+        command: &str,
+        member: &Member,
+    ) -> Result<(Permissions, bool)> {
+        let perms = member.permissions.unwrap();
+        match self.command_role(command).await? {
+            Some(role) if member.roles.contains(&role) => Ok((perms, true)),
+            Some(role) => Err(Error::User(format!(
+                "You need the {} role to use this command.",
+                role.mention()
+            ))),
+            None => Ok((perms, false)),
+        }
+    }
+
+    /// The role `/commandrole` gave `command`, if any.
+    async fn command_role(&self, command: &str) -> Result<Option<RoleId>> {
+        Ok(self
+            .config
+            .get(CommandPermissions)
+            .await?
+            .and_then(|roles| roles.0.get(command).copied()))
+    }
+
+    /// Whether `user` may do what `command` does by reacting in `channel`. Like
+    /// [`Bot::command_permissions`], the command's role replaces the `required` permissions.
+    async fn may_react(
+        &self,
+        ctx: &Context,
+        command: &str,
+        channel: ChannelId,
+        user: UserId,
+        required: Permissions,
+    ) -> Result<bool> {
+        match self.command_role(command).await? {
+            Some(role) => Ok(self
+                .guild()
+                .member(ctx, user)
+                .await
+                .map_err(anyhow::Error::from)?
+                .roles
+                .contains(&role)),
+            None => Ok(self
+                .permissions_in(ctx, channel, user)
+                .await?
+                .contains(required)),
+        }
+    }
+
+    async fn execute_command(
+        &self,
+        ctx: &Context,
+        cmd: &ApplicationCommandInteraction,
+    ) -> Result<Response> {
+        let (perms, by_role) = self
+            .command_permissions(&cmd.data.name, cmd.member.as_ref().unwrap())
+            .await?;
+        match cmd.data.name.as_str() {
+            "blockrole" => {
+                if !by_role && !perms.manage_roles() {
+                    return Err(Error::User(
+                        "You don't have `Manage Roles` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let role = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::Role(role) = role {
+                            if let Some(reason) = self.unassignable(ctx, role).await? {
+                                return Err(Error::User(format!(
+                                    "I can't assign `{}`: {}",
+                                    role.name, reason
+                                )));
+                            }
+
+                            self.config.set(Blockrole, role.id).await?;
+                            Ok(format!("Set block role to `{}`.", role.name.as_str()).into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(Blockrole).await?;
+                        Ok("Unset block role.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "inbox" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let raw = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::Channel(channel) = raw {
+                            self.check_inbox_channel(ctx, channel).await?;
+                            self.config.set(Inbox, channel.id).await?;
+                            Ok(format!("Set inbox to {}.", channel.id.mention()).into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(Inbox).await?;
+                        Ok("Unset inbox.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "migrate-inbox" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let channel = match option(&cmd.data.options, "channel") {
+                    Some(OptionValue::Channel(channel)) => channel,
+                    _ => panic!("got wrong option value"),
+                };
+                let move_threads = !matches!(
+                    option(&cmd.data.options, "move"),
+                    Some(OptionValue::Boolean(false))
+                );
+
+                self.check_inbox_channel(ctx, channel).await?;
+                let old = self.config.get(Inbox).await?;
+                if old == Some(channel.id) {
+                    return Err(Error::User(format!(
+                        "{} is already the inbox.",
+                        channel.id.mention()
+                    )));
+                }
+                self.config.set(Inbox, channel.id).await?;
+                if !move_threads {
+                    return Ok(format!("Set inbox to {}.", channel.id.mention()).into());
+                }
+
+                // threads in the overflow inbox stay where they are
+                let (mut moved, mut failed) = (0, 0);
+                for mut room in Room::all(&self.pool).await? {
+                    if old.is_some() && room.inbox_id != old {
+                        continue;
+                    }
+                    match self.move_room(ctx, &mut room, channel.id).await {
+                        Ok(()) => moved += 1,
+                        Err(err) => {
+                            tracing::warn!(source = ?err, codename = %room.codename, "Failed to move room.");
+                            failed += 1;
+                        }
+                    }
+                }
+
+                let mut reply = format!(
+                    "Set inbox to {} and moved {} thread(s) there.",
+                    channel.id.mention(),
+                    moved
+                );
+                if failed > 0 {
+                    reply.push_str(&format!(
+                        " {} thread(s) couldn't be moved and stay where they are.",
+                        failed
+                    ));
+                }
+                Ok(reply.into())
+            }
+
+            "block" => {
+                if !by_role && !perms.manage_roles() {
+                    return Err(Error::User(
+                        "You don't have `Manage Roles` permission.".into(),
+                    ));
+                }
+
+                let codename = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(codename) = codename {
+                    let room = self.room_from_codename(codename).await?;
+                    self.block_user(ctx, room.user_id).await?;
+                    Ok(format!("Blocked `{}`.", &codename).into())
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "blockstrip" => {
+                if !by_role && !perms.manage_roles() {
+                    return Err(Error::User(
+                        "You don't have `Manage Roles` permission.".into(),
+                    ));
+                }
+
+                let mut roles = self.config.get(BlockStripsRoles).await?.unwrap_or_default();
+                let sub = cmd.data.options.first().unwrap();
+                let role = match option(&sub.options, "role") {
+                    Some(OptionValue::Role(role)) => role,
+                    _ => panic!("got wrong option value"),
+                };
+                match sub.name.as_str() {
+                    "add" => {
+                        if let Some(reason) = self.unassignable(ctx, role).await? {
+                            return Err(Error::User(format!(
+                                "I can't remove `{}`: {}",
+                                role.name, reason
+                            )));
+                        }
+                        if !roles.0.contains(&role.id) {
+                            roles.0.push(role.id);
+                        }
+                    }
+                    "remove" => roles.0.retain(|id| *id != role.id),
+                    _ => {
+                        return Err(Error::UnknownCommand(format!(
+                            "{} {}",
+                            &cmd.data.name, &sub.name
+                        )))
+                    }
+                }
+
+                if roles.0.is_empty() {
+                    self.config.unset(BlockStripsRoles).await?;
+                    Ok("Blocking no longer removes any other roles.".into())
+                } else {
+                    let mentions: Vec<_> =
+                        roles.0.iter().map(|id| id.mention().to_string()).collect();
+                    self.config.set(BlockStripsRoles, roles).await?;
+                    Ok(format!("Blocking now also removes {}.", mentions.join(", ")).into())
+                }
+            }
+
+            "unblock-all" => {
+                if !by_role && !perms.manage_roles() {
+                    return Err(Error::User(
+                        "You don't have `Manage Roles` permission.".into(),
+                    ));
+                }
+
+                let role = self.config.get(Blockrole).await.and_then(|opt| {
+                    opt.ok_or_else(|| Error::User("There's no block role defined.".into()))
+                })?;
+                Ok(Response {
+                    content: format!(
+                        "This takes {} from every member who has it and unblocks them. Press the button within {} minutes to go ahead.",
+                        role.mention(),
+                        confirm::CONFIRM_TIMEOUT_MINUTES
+                    ),
+                    attachment: None,
+                    page: None,
+                    confirm: Some(Confirmation::new("unblock-all", cmd.user.id)),
+                })
+            }
+
+            "unblock" => {
+                if !by_role && !perms.manage_roles() {
+                    return Err(Error::User(
+                        "You don't have `Manage Roles` permission.".into(),
+                    ));
+                }
+
+                let codename = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(codename) = codename {
+                    let room = self.room_from_codename(codename).await?;
+
+                    let restore = !matches!(
+                        option(&cmd.data.options, "restore"),
+                        Some(OptionValue::Boolean(false))
+                    );
+                    let stripped = Block::stripped_roles(&self.pool, room.user_id).await?;
+                    let member = match self.guild().member(ctx, room.user_id).await {
+                        Ok(member) => Some(member),
+                        Err(err) if ratelimit::unknown_member(&err) => None,
+                        Err(err) => return Err(anyhow::Error::from(err).into()),
+                    };
+                    if let Some(mut member) = member {
+                        if let Some(role) = self.config.get(Blockrole).await? {
+                            if member.roles.contains(&role) {
+                                member.remove_role(ctx, role).await.map_err(|_| {
+                                    Error::User(
+                                        "Missing permissions or configured block role is invalid."
+                                            .into(),
+                                    )
+                                })?;
+                            }
+                        }
+
+                        if restore && !stripped.is_empty() {
+                            member.add_roles(ctx, &stripped).await.map_err(|_| {
+                                Error::User(
+                                    "Unblocked, but I'm missing permissions to give back their other roles."
+                                        .into(),
+                                )
+                            })?;
+                        }
+                    }
+
+                    // only forget the block, and with it the stripped roles, once they're back
+                    let blocked = Block::remove(&self.pool, room.user_id).await?;
+                    if blocked {
+                        Ok(format!("Unblocked `{}`.", &codename).into())
+                    } else {
+                        Ok(format!("`{}` wasn't blocked.", &codename).into())
+                    }
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "remind" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let delay = match option(&cmd.data.options, "duration") {
+                    Some(OptionValue::String(delay)) => duration::parse(delay).map_err(|err| {
+                        Error::User(format!("Invalid duration `{}`: {}.", delay, err))
+                    })?,
+                    _ => panic!("got wrong option value"),
+                };
+
+                let room = self.room_from_command(cmd).await?;
+
+                let due_at = Utc::now()
+                    .checked_add_signed(delay)
+                    .ok_or_else(|| Error::User("That's too far in the future.".into()))?;
+                Reminder::new(&self.pool, &room, cmd.user.id, due_at).await?;
+                Ok(format!(
+                    "I'll remind you about `{}` <t:{}:R>.",
+                    &room.codename,
+                    due_at.timestamp()
+                )
+                .into())
+            }
+
+            "schedule" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "add" => {
+                        let (delay, message) = match (
+                            option(&sub.options, "duration"),
+                            option(&sub.options, "message"),
+                        ) {
+                            (
+                                Some(OptionValue::String(delay)),
+                                Some(OptionValue::String(message)),
+                            ) => (
+                                duration::parse(delay).map_err(|err| {
+                                    Error::User(format!("Invalid duration `{}`: {}.", delay, err))
+                                })?,
+                                message,
+                            ),
+                            _ => panic!("got wrong option value"),
+                        };
+
+                        let room = self.room_from_options(&sub.options, cmd.channel_id).await?;
+                        let due_at = Utc::now()
+                            .checked_add_signed(delay)
+                            .ok_or_else(|| Error::User("That's too far in the future.".into()))?;
+                        let content = MessageBuilder::new().push_safe(message).build();
+                        let scheduled =
+                            ScheduledMessage::new(&self.pool, &room, cmd.user.id, content, due_at)
+                                .await?;
+                        Ok(format!(
+                            "Scheduled message `#{}` will be sent to `{}` <t:{}:R>.",
+                            scheduled.scheduled_id,
+                            &room.codename,
+                            due_at.timestamp()
+                        )
+                        .into())
+                    }
+
+                    "list" => {
+                        let room = self.room_from_options(&sub.options, cmd.channel_id).await?;
+                        let scheduled =
+                            ScheduledMessage::for_room(&self.pool, room.room_id).await?;
+                        if scheduled.is_empty() {
+                            return Ok(
+                                format!("`{}` has no scheduled messages.", &room.codename).into()
+                            );
+                        }
+
+                        Ok(scheduled
+                            .iter()
+                            .map(|scheduled| {
+                                format!(
+                                    "`#{}` <t:{}:R> by {}: {}",
+                                    scheduled.scheduled_id,
+                                    scheduled.due_at.timestamp(),
+                                    scheduled.author_id.mention(),
+                                    &scheduled.content
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                            .into())
+                    }
+
+                    "cancel" => {
+                        let id = match option(&sub.options, "id") {
+                            Some(OptionValue::Integer(id)) => *id,
+                            _ => panic!("got wrong option value"),
+                        };
+                        match ScheduledMessage::get(&self.pool, id).await? {
+                            Some(scheduled) => {
+                                scheduled.delete(&self.pool).await?;
+                                Ok(format!("Cancelled scheduled message `#{}`.", id).into())
+                            }
+                            None => Err(Error::User(format!(
+                                "There's no scheduled message `#{}`.",
+                                id
+                            ))),
+                        }
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "transcriptlimit" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let raw = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::Integer(limit) = raw {
+                            self.config.set(TranscriptLimit, *limit as usize).await?;
+                            Ok(format!(
+                                "Transcripts will keep the last {} messages of a thread.",
+                                limit
+                            )
+                            .into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(TranscriptLimit).await?;
+                        Ok(format!(
+                            "Transcripts will keep the last {} messages of a thread.",
+                            DEFAULT_TRANSCRIPT_LIMIT
+                        )
+                        .into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "attach" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let user = match option(&cmd.data.options, "user") {
+                    Some(OptionValue::User(user, _)) => user,
+                    _ => panic!("got wrong option value"),
+                };
+                let thread = match option(&cmd.data.options, "thread") {
+                    Some(OptionValue::Channel(thread)) => thread.id,
+                    _ => cmd.channel_id,
+                };
+
+                let thread = thread
+                    .to_channel(ctx)
+                    .await
+                    .map_err(anyhow::Error::from)?
+                    .guild()
+                    .filter(|channel| {
+                        matches!(
+                            channel.kind,
+                            ChannelType::PublicThread
+                                | ChannelType::PrivateThread
+                                | ChannelType::NewsThread
+                        )
+                    })
+                    .ok_or_else(|| {
+                        Error::User("Use this command in a thread or pick one.".into())
+                    })?;
+
+                if user.bot {
+                    return Err(Error::User("Bots can't be attached to threads.".into()));
+                }
+                if Room::get_by_channel(&self.pool, thread.id).await?.is_some() {
+                    return Err(Error::User(format!(
+                        "{} already belongs to a user.",
+                        thread.id.mention()
+                    )));
+                }
+                if let Some(room) =
+                    Room::get_by_user_and_category(&self.pool, user.id, None).await?
+                {
+                    return Err(Error::User(format!(
+                        "{} already has a thread, `{}`.",
+                        user.mention(),
+                        &room.codename
+                    )));
+                }
+
+                // prefer the thread's name, so staff can keep referring to it
+                let name = codenames::sanitize(&thread.name);
+                let length = name.chars().count();
+                let codename = if (codenames::MIN_LEN..=codenames::MAX_LEN).contains(&length)
+                    && !Room::codename_exists(&self.pool, &name).await?
+                {
+                    name
+                } else {
+                    self.fresh_codename().await?
+                };
+
+                let mut room = Room::new(&self.pool, codename, thread.id, user.id).await?;
+                self.presence.notify_one();
+                self.audit(
+                    ctx,
+                    Event::Open,
+                    format!(
+                        "Attached {} to {} as `{}`.",
+                        user.mention(),
+                        thread.id.mention(),
+                        &room.codename
+                    ),
+                )
+                .await;
+                if let Some(parent) = thread.parent_id {
+                    room.set_inbox_id(&self.pool, parent).await?;
+                }
+                Ok(format!(
+                    "Attached {} to {} as `{}`.",
+                    user.mention(),
+                    thread.id.mention(),
+                    &room.codename
+                )
+                .into())
+            }
+
+            "set-codename" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let (user, codename) = match (
+                    option(&cmd.data.options, "user"),
+                    option(&cmd.data.options, "codename"),
+                ) {
+                    (Some(OptionValue::User(user, _)), Some(OptionValue::String(codename))) => {
+                        (user, codenames::sanitize(codename))
+                    }
+                    _ => panic!("got wrong option value"),
+                };
+
+                if !(codenames::MIN_LEN..=codenames::MAX_LEN).contains(&codename.chars().count()) {
+                    return Err(Error::User(format!(
+                        "Codenames must be between {} and {} characters long.",
+                        codenames::MIN_LEN,
+                        codenames::MAX_LEN
+                    )));
+                }
+                if self.codename_taken(&codename, user.id).await? {
+                    return Err(Error::User(format!(
+                        "The codename `{}` is already taken.",
+                        codename
+                    )));
+                }
+
+                Alias::set(&self.pool, user.id, &codename).await?;
+                // codenames are unique, so only one of the user's rooms can take it: the one that
+                // has it already, or else the most recently active one
+                let mut rooms = Room::for_user(&self.pool, user.id).await?;
+                rooms.sort_by_key(|room| (room.codename == codename, room.last_activity_at));
+                match rooms.pop() {
+                    Some(mut room) => {
+                        room.set_codename(&self.pool, codename.to_owned()).await?;
+                        self.rename_thread(ctx, &room).await;
+                        let mut reply = format!(
+                            "{} is now `{}`, in {} and all their future threads.",
+                            user.mention(),
+                            codename,
+                            room.channel_id.mention()
+                        );
+                        if !rooms.is_empty() {
+                            let others: Vec<_> = rooms
+                                .iter()
+                                .map(|room| format!("`{}`", room.codename))
+                                .collect();
+                            reply.push_str(&format!(
+                                " Their other open thread(s) keep their codenames: {}",
+                                others.join(", ")
+                            ));
+                        }
+                        Ok(reply.into())
+                    }
+                    None => Ok(format!(
+                        "{} will be `{}` in all their future threads.",
+                        user.mention(),
+                        codename
+                    )
+                    .into()),
+                }
+            }
+
+            "claim" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let mut room = self.room_from_command(cmd).await?;
+                match room.claimed_by {
+                    Some(staff) if staff == cmd.user.id => {
+                        Ok(format!("You've already claimed `{}`.", &room.codename).into())
+                    }
+                    Some(staff) => Err(Error::User(format!(
+                        "`{}` is already claimed by {}.",
+                        &room.codename,
+                        staff.mention()
+                    ))),
+                    None => {
+                        room.set_claimed_by(&self.pool, Some(cmd.user.id)).await?;
+                        Ok(format!(
+                            "Claimed `{}`. Only your messages will be forwarded to the user.",
+                            &room.codename
+                        )
+                        .into())
+                    }
+                }
+            }
+
+            "unclaim" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let mut room = self.room_from_command(cmd).await?;
+                match room.claimed_by {
+                    None => Ok(format!("`{}` isn't claimed.", &room.codename).into()),
+                    Some(staff) if staff != cmd.user.id && !perms.manage_guild() => {
+                        Err(Error::User(format!(
+                            "Only {} or someone with `Manage Server` permission can unclaim `{}`.",
+                            staff.mention(),
+                            &room.codename
+                        )))
+                    }
+                    Some(_) => {
+                        room.set_claimed_by(&self.pool, None).await?;
+                        Ok(format!("Unclaimed `{}`.", &room.codename).into())
+                    }
+                }
+            }
+
+            "reassign-from" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let from = match option(&cmd.data.options, "user") {
+                    Some(OptionValue::User(user, _)) => user.id,
+                    _ => panic!("got wrong option value"),
+                };
+                let to = match option(&cmd.data.options, "to") {
+                    Some(OptionValue::User(user, _)) => Some(user.id),
+                    Some(_) => panic!("got wrong option value"),
+                    None => None,
+                };
+
+                let count = Room::reassign_claims(&self.pool, from, to).await?;
+                Ok(match to {
+                    Some(to) => format!(
+                        "Handed {} thread(s) claimed by {} to {}.",
+                        count,
+                        from.mention(),
+                        to.mention()
+                    ),
+                    None => format!(
+                        "Unclaimed {} thread(s) claimed by {}.",
+                        count,
+                        from.mention()
+                    ),
+                }
+                .into())
+            }
+
+            "mute-notifications" => {
+                if NotificationOptout::toggle(&self.pool, cmd.user.id).await? {
+                    Ok("You'll no longer be pinged when users reply to a quiet thread.".into())
+                } else {
+                    Ok("You'll be pinged again when users reply to a quiet thread.".into())
+                }
+            }
+
+            "snippet" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                let name = match option(&sub.options, "name") {
+                    Some(OptionValue::String(name)) => Some(name),
+                    Some(_) => panic!("got wrong option value"),
+                    None => None,
+                };
+                let content = match option(&sub.options, "content") {
+                    Some(OptionValue::String(content)) => Some(content),
+                    Some(_) => panic!("got wrong option value"),
+                    None => None,
+                };
+
+                match (sub.name.as_str(), name, content) {
+                    ("add", Some(name), Some(content)) => {
+                        if Snippet::get(&self.pool, name).await?.is_some() {
+                            return Err(Error::User(format!(
+                                "A snippet named `{}` already exists, use `/snippet edit` to change it.",
+                                name
+                            )));
+                        }
+
+                        Snippet::new(&self.pool, name.clone(), content.clone()).await?;
+                        Ok(format!("Added snippet `{}`.", name).into())
+                    }
+
+                    ("edit", Some(name), Some(content)) => {
+                        let mut snippet =
+                            Snippet::get(&self.pool, name).await?.ok_or_else(|| {
+                                Error::User(format!("No snippet named `{}` found.", name))
+                            })?;
+                        snippet.set_content(&self.pool, content.clone()).await?;
+                        Ok(format!("Updated snippet `{}`.", name).into())
+                    }
+
+                    ("emoji", Some(name), _) => {
+                        let mut snippet =
+                            Snippet::get(&self.pool, name).await?.ok_or_else(|| {
+                                Error::User(format!("No snippet named `{}` found.", name))
+                            })?;
+                        match option(&sub.options, "emoji") {
+                            Some(OptionValue::String(emoji)) => {
+                                let emoji = emoji.trim().to_owned();
+                                let reply = format!(
+                                    "Reacting with {} to a thread's message in the inbox will send `{}`.",
+                                    emoji, name
+                                );
+                                snippet.set_emoji(&self.pool, Some(emoji)).await?;
+                                Ok(reply.into())
+                            }
+                            None => {
+                                snippet.set_emoji(&self.pool, None).await?;
+                                Ok(format!("`{}` can no longer be sent with a reaction.", name)
+                                    .into())
+                            }
+                            _ => panic!("got wrong option value"),
+                        }
+                    }
+
+                    ("remove", Some(name), _) => {
+                        if Snippet::delete(&self.pool, name).await? {
+                            Ok(format!("Removed snippet `{}`.", name).into())
+                        } else {
+                            Err(Error::User(format!("No snippet named `{}` found.", name)))
+                        }
+                    }
+
+                    ("list", _, _) => self.show_page(Page::first("snippets")).await,
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "reply" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let mut room = Room::get_by_channel(&self.pool, cmd.channel_id)
+                    .await?
+                    .ok_or_else(|| Error::User("Use this command in a thread.".into()))?;
+                if let Some(staff) = room.claimed_by {
+                    if staff != cmd.user.id {
+                        return Err(Error::User(format!(
+                            "This thread is claimed by {}.",
+                            staff.mention()
+                        )));
+                    }
+                }
+
+                let raw = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(name) = raw {
+                    let snippet = Snippet::get(&self.pool, name).await?.ok_or_else(|| {
+                        Error::User(format!("No snippet named `{}` found.", name))
+                    })?;
+                    // a snippet has no message in the thread that could be queued until `/resume`
+                    if room.pause.is_some() {
+                        return Err(Error::User(format!(
+                            "`{}` is paused, so the snippet wasn't sent.",
+                            &room.codename
+                        )));
+                    }
+
+                    let user = room
+                        .user_id
+                        .to_user(ctx)
+                        .await
+                        .map_err(anyhow::Error::from)?;
+                    let content = template::render(
+                        &snippet.content,
+                        &[("codename", &room.codename), ("user", &user.name)],
+                    );
+                    self.forward_to_user(ctx, &mut room, &content, &[], None)
+                        .await?;
+                    Ok(content.into())
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "pause" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let pause = match option(&cmd.data.options, "drop") {
+                    Some(OptionValue::Boolean(true)) => Pause::Drop,
+                    _ => Pause::Queue,
+                };
+
+                let mut room = self.room_from_command(cmd).await?;
+                if room.pause.is_some() {
+                    return Err(Error::User(format!(
+                        "`{}` is already paused.",
+                        &room.codename
+                    )));
+                }
+
+                room.set_pause(&self.pool, Some(pause)).await?;
+                self.rename_thread(ctx, &room).await;
+                Ok(match pause {
+                    Pause::Queue => format!(
+                        "Paused `{}`. Messages sent here will reach the user on `/resume`.",
+                        &room.codename
+                    ),
+                    Pause::Drop => format!(
+                        "Paused `{}`. Messages sent here won't reach the user.",
+                        &room.codename
+                    ),
+                }
+                .into())
+            }
+
+            "snooze" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let delay = match option(&cmd.data.options, "duration") {
+                    Some(OptionValue::String(delay)) => duration::parse(delay).map_err(|err| {
+                        Error::User(format!("Invalid duration `{}`: {}.", delay, err))
+                    })?,
+                    _ => panic!("got wrong option value"),
+                };
+
+                let mut room = self.room_from_command(cmd).await?;
+
+                let until = Utc::now()
+                    .checked_add_signed(delay)
+                    .ok_or_else(|| Error::User("That's too far in the future.".into()))?;
+                room.set_snoozed_until(&self.pool, Some(until)).await?;
+                if let Some(OptionValue::Boolean(true)) = option(&cmd.data.options, "ping") {
+                    Reminder::new(&self.pool, &room, cmd.user.id, until).await?;
+                }
+                Ok(format!(
+                    "Snoozed `{}`, it'll be back in `/list` <t:{}:R>.",
+                    &room.codename,
+                    until.timestamp()
+                )
+                .into())
+            }
+
+            "unsnooze" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let mut room = self.room_from_command(cmd).await?;
+                if !room.snoozed(Utc::now()) {
+                    return Err(Error::User(format!("`{}` isn't snoozed.", &room.codename)));
+                }
+
+                room.set_snoozed_until(&self.pool, None).await?;
+                Ok(format!("`{}` is back in `/list`.", &room.codename).into())
+            }
+
+            "merge" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let (source, target) = match (
+                    option(&cmd.data.options, "source"),
+                    option(&cmd.data.options, "target"),
+                ) {
+                    (Some(OptionValue::String(source)), Some(OptionValue::String(target))) => (
+                        self.room_from_codename(source).await?,
+                        self.room_from_codename(target).await?,
+                    ),
+                    _ => panic!("got wrong option value"),
+                };
+                if source.room_id == target.room_id {
+                    return Err(Error::User("Can't merge a thread into itself.".into()));
+                }
+                // users only have several rooms once they're split by `categories`
+                if source.user_id != target.user_id {
+                    return Err(Error::User(format!(
+                        "`{}` and `{}` belong to different users.",
+                        &source.codename, &target.codename
+                    )));
+                }
+                // queued messages would be lost along with the room
+                if source.pause.is_some() {
+                    return Err(Error::User(format!(
+                        "`{}` is paused, `/resume` it first.",
+                        &source.codename
+                    )));
+                }
+
+                let (messages, truncated) =
+                    transcript::fetch(&ctx.http, source.channel_id, MERGE_CONTEXT_LIMIT)
+                        .await
+                        .map_err(anyhow::Error::from)?;
+                let bot = ctx.cache.current_user_id();
+                let lines: Vec<_> = messages
+                    .iter()
+                    .map(|msg| transcript_line(msg, bot, &source.codename))
+                    .collect();
+                let context = transcript::render(&lines, truncated);
+                target
+                    .channel_id
+                    .send_message(ctx, |msg| {
+                        msg.content(format!(
+                            "Merged `{}` ({}) into this thread, its recent messages are attached.",
+                            &source.codename,
+                            source.channel_id.mention()
+                        ))
+                        .add_file(AttachmentType::Bytes {
+                            data: context.into_bytes().into(),
+                            filename: format!("merged-{}.txt", source.codename.replace(' ', "-")),
+                        })
+                    })
+                    .await
+                    .map_err(anyhow::Error::from)?;
+
+                let _ = source
+                    .channel_id
+                    .send_message(ctx, |msg| {
+                        msg.content(format!(
+                            "Merged into `{}` ({}).",
+                            &target.codename,
+                            target.channel_id.mention()
+                        ))
+                    })
+                    .await;
+                let _ = source
+                    .channel_id
+                    .edit_thread(ctx, |edit| edit.archived(true))
+                    .await;
+
+                // these would be lost along with the room otherwise
+                let reminders = Reminder::move_all(&self.pool, &source, &target).await?;
+                let scheduled = ScheduledMessage::move_all(&self.pool, &source, &target).await?;
+
+                let codename = source.codename.clone();
+                source.delete(&self.pool).await?;
+                self.presence.notify_one();
+                Ok(format!(
+                    "Merged `{}` into `{}`, moving {} reminder(s) and {} scheduled message(s) along.",
+                    codename, &target.codename, reminders, scheduled
+                )
+                .into())
+            }
+
+            "resume" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let mut room = self.room_from_command(cmd).await?;
+                if room.pause.is_none() {
+                    return Err(Error::User(format!("`{}` isn't paused.", &room.codename)));
+                }
+
+                let sent = self.resume(ctx, &mut room).await?;
+                Ok(format!(
+                    "Resumed `{}` and sent {} queued message(s).",
+                    &room.codename, sent
+                )
+                .into())
+            }
+
+            "threadlink" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let codename = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(codename) = codename {
+                    let room = self.room_from_codename(codename).await?;
+                    Ok(format!(
+                        "[`{}`](https://discord.com/channels/{}/{})",
+                        &room.codename,
+                        self.guild(),
+                        room.channel_id
+                    )
+                    .into())
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "broadcast" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let message = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(message) = message {
+                    let content = MessageBuilder::new().push_safe(message).build();
+                    // users with rooms in several categories are only messaged once
+                    let mut users: Vec<(UserId, Vec<Room>)> = Vec::new();
+                    for room in Room::all(&self.pool).await? {
+                        match users.iter_mut().find(|(user, _)| *user == room.user_id) {
+                            Some((_, rooms)) => rooms.push(room),
+                            None => users.push((room.user_id, vec![room])),
+                        }
+                    }
+                    if self.broadcasting.swap(true, Ordering::SeqCst) {
+                        return Err(Error::User("A broadcast is already being sent.".into()));
+                    }
+
+                    let count = users.len();
+                    tokio::spawn(broadcast(
+                        ctx.http.clone(),
+                        content,
+                        users,
+                        cmd.channel_id,
+                        self.broadcasting.clone(),
+                    ));
+                    Ok(format!(
+                        "Broadcasting to {} user(s). This can take a while, so progress is posted in this channel.",
+                        count
+                    )
+                    .into())
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "close" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let codename = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(codename) = codename {
+                    let room = self.room_from_codename(codename).await?;
+
+                    let reason = match option(&cmd.data.options, "reason") {
+                        Some(OptionValue::String(reason)) => reason.as_str(),
+                        _ => "No reason provided.",
+                    };
+                    let filename = format!("transcript-{}.txt", room.codename.replace(' ', "-"));
+                    let thread = room.channel_id;
+                    let transcript = self.close_room(ctx, room, reason).await?;
+
+                    let mut content =
+                        format!("Archived `{}` and removed attached user.", &codename);
+                    if let Some((saved, _)) = &transcript {
+                        content.push_str(&format!(" Saved transcript `#{}`.", saved.transcript_id));
+                    }
+
+                    // the thread is archived like any other until deleting it is confirmed
+                    let delete = matches!(
+                        option(&cmd.data.options, "delete"),
+                        Some(OptionValue::Boolean(true))
+                    );
+                    if delete {
+                        content.push_str(&format!(
+                            "\n\nPress the button within {} minutes to delete {} for good. \
+                            This can't be undone.",
+                            confirm::CONFIRM_TIMEOUT_MINUTES,
+                            thread.mention()
+                        ));
+                    }
+
+                    Ok(Response {
+                        content,
+                        attachment: transcript.map(|(_, t)| (t.into_bytes(), filename)),
+                        page: None,
+                        confirm: delete
+                            .then(|| Confirmation::new("close", cmd.user.id).with_target(thread.0)),
+                    })
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "close-all-inactive" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let idle = match option(&cmd.data.options, "idle") {
+                    Some(OptionValue::String(idle)) => duration::parse(idle).map_err(|err| {
+                        Error::User(format!("Invalid duration `{}`: {}.", idle, err))
+                    })?,
+                    _ => panic!("got wrong option value"),
+                };
+                let dry_run = !matches!(
+                    option(&cmd.data.options, "dry_run"),
+                    Some(OptionValue::Boolean(false))
+                );
+
+                let cutoff = Utc::now()
+                    .checked_sub_signed(idle)
+                    .ok_or_else(|| Error::User("That's too far in the past.".into()))?;
+                let inactive: Vec<_> = Room::all(&self.pool)
+                    .await?
+                    .into_iter()
+                    .filter(|room| room.last_activity_at < cutoff)
+                    .collect();
+                if inactive.is_empty() {
+                    return Ok(format!(
+                        "No threads have been inactive since <t:{}:R>.",
+                        cutoff.timestamp()
+                    )
+                    .into());
+                }
+
+                let codenames: Vec<_> = inactive
+                    .iter()
+                    .map(|room| format!("`{}`", room.codename))
+                    .collect();
+                if dry_run {
+                    return Ok(format!(
+                        "Would close {} thread(s): {}\n\nRun again with `dry_run: False` to close them.",
+                        inactive.len(),
+                        codenames.join(", ")
+                    )
+                    .into());
+                }
+
+                let (mut closed, mut failed) = (Vec::new(), Vec::new());
+                for (room, codename) in inactive.into_iter().zip(codenames) {
+                    let res = self.close_room(ctx, room, "Closed for inactivity.").await;
+                    match res {
+                        Ok(_) => closed.push(codename),
+                        Err(err) => {
+                            tracing::warn!(source = ?err, codename = %codename, "Failed to close inactive room.");
+                            failed.push(codename);
+                        }
+                    }
+                }
+
+                let mut reply = format!("Closed {} thread(s): {}", closed.len(), closed.join(", "));
+                if !failed.is_empty() {
+                    reply.push_str(&format!("\nFailed: {}", failed.join(", ")));
+                }
+                Ok(reply.into())
+            }
+
+            "maxthreads" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let max = match option(&sub.options, "max") {
+                            Some(OptionValue::Integer(max)) => *max,
+                            _ => panic!("got wrong option value"),
+                        };
+                        match option(&sub.options, "overflow") {
+                            Some(OptionValue::Channel(overflow)) => {
+                                if !matches!(overflow.kind, ChannelType::Text | ChannelType::News) {
+                                    return Err(Error::User(
+                                        "The overflow inbox must be a text or announcement channel."
+                                            .into(),
+                                    ));
+                                }
+
+                                let missing =
+                                    self.missing_inbox_permissions(ctx, overflow.id).await?;
+                                if !missing.is_empty() {
+                                    return Err(Error::User(format!(
+                                        "I'm missing these permissions in {}: {}.",
+                                        overflow.id.mention(),
+                                        missing
+                                    )));
+                                }
+
+                                self.config.set(MaxOpenThreads, max).await?;
+                                self.config.set(OverflowInbox, overflow.id).await?;
+                                Ok(format!(
+                                    "Once the inbox has {} open thread(s), new threads will go to {}.",
+                                    max,
+                                    overflow.id.mention()
+                                )
+                                .into())
+                            }
+                            _ => {
+                                self.config.set(MaxOpenThreads, max).await?;
+                                self.config.unset(OverflowInbox).await?;
+                                Ok(format!(
+                                    "Once the inbox has {} open thread(s), new users will be asked to try again later.",
+                                    max
+                                )
+                                .into())
+                            }
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(MaxOpenThreads).await?;
+                        self.config.unset(OverflowInbox).await?;
+                        Ok("The inbox will take any number of threads.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "closedm" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let raw = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::String(template) = raw {
+                            self.config.set(CloseDm, template.clone()).await?;
+                            Ok("Set close message.".into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(CloseDm).await?;
+                        Ok("Unset close message.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "filter" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "add" => {
+                        let raw = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::String(pattern) = raw {
+                            Regex::new(pattern)
+                                .map_err(|err| Error::User(format!("Invalid pattern: {}", err)))?;
+                            if Filter::exists(&self.pool, pattern).await? {
+                                return Err(Error::User("That filter already exists.".into()));
+                            }
+
+                            let filter = Filter::new(&self.pool, pattern.clone()).await?;
+                            Ok(format!("Added filter #{}.", filter.filter_id).into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "remove" => {
+                        let raw = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::Integer(id) = raw {
+                            if Filter::delete(&self.pool, *id).await? {
+                                Ok(format!("Removed filter #{}.", id).into())
+                            } else {
+                                Err(Error::User(format!("No filter #{} found.", id)))
+                            }
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "list" => {
+                        let filters = Filter::all(&self.pool).await?;
+                        if filters.is_empty() {
+                            return Ok("No filters are set.".into());
+                        }
+
+                        Ok(filters
+                            .iter()
+                            .map(|f| format!("#{}: `{}`", f.filter_id, f.pattern))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                            .into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "forget" => {
+                if cmd.user.id != self.owner && !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let user = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::User(user, _) = user {
+                    let rooms = Room::for_user(&self.pool, user.id).await?;
+                    let erasure = Erasure::forget(&self.pool, user.id).await?;
+                    for room in rooms {
+                        self.batcher.flush(room.room_id);
+                    }
+
+                    // the rooms are gone already, so `thread_delete` has nothing left to clean up
+                    let mut kept = 0;
+                    for thread in &erasure.threads {
+                        if let Err(err) = thread.delete(ctx).await {
+                            tracing::warn!(source = ?err, thread = %thread, "Failed to delete a forgotten thread.");
+                            kept += 1;
+                        }
+                    }
+
+                    let mut reply = format!(
+                        "Forgot {}: removed {} room(s) and their threads, {} failed message(s), {} reminder(s), {} transcript(s), {} block(s) and {} codename(s).",
+                        user.mention(),
+                        erasure.threads.len(),
+                        erasure.dead_letters,
+                        erasure.reminders,
+                        erasure.transcripts,
+                        erasure.blocks,
+                        erasure.aliases
+                    );
+                    if kept > 0 {
+                        reply.push_str(&format!(
+                            "\n{} thread(s) couldn't be deleted and have to be deleted by hand.",
+                            kept
+                        ));
+                    }
+                    Ok(reply.into())
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "footer" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let show = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::Boolean(show) = show {
+                    self.config.set(ShowFooter, *show).await?;
+                    if *show {
+                        Ok("The bot's responses will be signed off with a footer.".into())
+                    } else {
+                        Ok("The bot's responses will no longer have a footer.".into())
+                    }
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "analytics" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let record = match option(&cmd.data.options, "record") {
+                    Some(OptionValue::Boolean(record)) => *record,
+                    _ => panic!("got wrong option value"),
+                };
+                self.config.set(Analytics, record).await?;
+                if record {
+                    Ok("Closed threads will be counted for `/report`, without their users.".into())
+                } else {
+                    Ok("Closed threads will no longer be counted for `/report`.".into())
+                }
+            }
+
+            "report" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+                if !self.config.get(Analytics).await?.unwrap_or(false) {
+                    return Err(Error::User(
+                        "Analytics are off, turn them on with `/analytics record: True`.".into(),
+                    ));
+                }
+
+                let period = match option(&cmd.data.options, "period") {
+                    Some(OptionValue::String(period)) => {
+                        duration::parse(period).map_err(|err| {
+                            Error::User(format!("Invalid duration `{}`: {}.", period, err))
+                        })?
+                    }
+                    _ => panic!("got wrong option value"),
+                };
+                let since = Utc::now()
+                    .checked_sub_signed(period)
+                    .ok_or_else(|| Error::User("That's too far in the past.".into()))?;
+
+                let report = Report::since(&self.pool, since).await?;
+                let busiest = if report.busiest_hours.is_empty() {
+                    "none".to_owned()
+                } else {
+                    report
+                        .busiest_hours
+                        .iter()
+                        .map(|(hour, count)| format!("{:02}:00 ({})", hour, count))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                Ok(format!(
+                    "**Since <t:{}:f>**\nThreads opened: {}\nThreads closed: {}\n\
+                    Median first response: {}\nBusiest hours (UTC): {}",
+                    since.timestamp(),
+                    report.opened,
+                    report.closed,
+                    report
+                        .median_first_response
+                        .map_or_else(|| "no replies yet".to_owned(), duration::format),
+                    busiest
+                )
+                .into())
+            }
+
+            "presence" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let show = match option(&cmd.data.options, "show") {
+                    Some(OptionValue::Boolean(show)) => *show,
+                    _ => panic!("got wrong option value"),
+                };
+                self.config.set(ShowThreadCount, show).await?;
+                self.presence.notify_one();
+                if show {
+                    Ok("The bot's status will show how many threads are open.".into())
+                } else {
+                    Ok("The bot's status will no longer show how many threads are open.".into())
+                }
+            }
+
+            "officehours" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let (start, end, timezone) = match (
+                            option(&sub.options, "start"),
+                            option(&sub.options, "end"),
+                            option(&sub.options, "timezone"),
+                        ) {
+                            (
+                                Some(OptionValue::String(start)),
+                                Some(OptionValue::String(end)),
+                                Some(OptionValue::String(timezone)),
+                            ) => (start, end, timezone),
+                            _ => panic!("got wrong option value"),
+                        };
+
+                        let schedule: Schedule = format!("{}-{} {}", start, end, timezone)
+                            .parse()
+                            .map_err(|err| {
+                                Error::User(format!("Invalid office hours: {}.", err))
+                            })?;
+
+                        match option(&sub.options, "message") {
+                            Some(OptionValue::String(message)) => {
+                                self.config.set(OutOfHoursMessage, message.clone()).await?
+                            }
+                            _ => self.config.unset(OutOfHoursMessage).await?,
+                        }
+
+                        let hold = matches!(
+                            option(&sub.options, "hold"),
+                            Some(OptionValue::Boolean(true))
+                        );
+                        self.config.set(OutOfHoursHold, hold).await?;
+
+                        let reply = format!(
+                            "Set office hours to `{}`. Outside of them, new threads will be {}.",
+                            schedule,
+                            if hold { "refused" } else { "flagged" }
+                        );
+                        self.config.set(OfficeHours, schedule).await?;
+                        Ok(reply.into())
+                    }
+
+                    "unset" => {
+                        self.config.unset(OfficeHours).await?;
+                        self.config.unset(OutOfHoursMessage).await?;
+                        self.config.unset(OutOfHoursHold).await?;
+                        Ok("Unset office hours.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "coalesce" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let raw = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::Integer(window) = raw {
+                            self.config.set(CoalesceWindow, *window as u64).await?;
+                            Ok(format!(
+                                "Messages sent within {}ms of each other will be forwarded together.",
+                                window
+                            )
+                            .into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(CoalesceWindow).await?;
+                        Ok("Messages will be forwarded one by one.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "codename" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let command = match option(&sub.options, "command") {
+                            Some(OptionValue::String(command)) => command.trim().to_owned(),
+                            _ => DEFAULT_CODENAME_COMMAND.to_owned(),
+                        };
+                        if command.is_empty() || command.contains(char::is_whitespace) {
+                            return Err(Error::User("The command must be a single word.".into()));
+                        }
+
+                        let reply = format!(
+                            "Users can now pick a codename by starting their first message with `{} <codename>`.",
+                            command
+                        );
+                        self.config.set(CodenameCommand, command).await?;
+                        Ok(reply.into())
+                    }
+
+                    "unset" => {
+                        self.config.unset(CodenameCommand).await?;
+                        Ok("Users can no longer pick their own codename.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "mentions" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "set" => {
+                        let role = sub.options.first().unwrap().resolved.as_ref().unwrap();
+                        if let OptionValue::Role(role) = role {
+                            self.config.set(ReplyPingRole, role.id).await?;
+                            Ok(format!(
+                                "`{}` will be pinged when users reply to a quiet thread.",
+                                role.name.as_str()
+                            )
+                            .into())
+                        } else {
+                            panic!("got wrong option value")
+                        }
+                    }
+
+                    "unset" => {
+                        self.config.unset(ReplyPingRole).await?;
+                        Ok("Users' replies will no longer ping anyone.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "reopensummary" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let show = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::Boolean(show) = show {
+                    self.config.set(ReopenSummary, *show).await?;
+                    if *show {
+                        Ok("Reopened threads will start with a summary of what was missed.".into())
+                    } else {
+                        Ok("Reopened threads will no longer get a summary.".into())
+                    }
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "revealusername" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                match option(&cmd.data.options, "show") {
+                    Some(OptionValue::Boolean(show)) => {
+                        self.config.set(RevealUsername, *show).await?;
+                        if *show {
+                            Ok("New threads will be named after the codename and username.".into())
+                        } else {
+                            Ok("New threads will only be named after the codename.".into())
+                        }
+                    }
+                    _ => panic!("got wrong option value"),
+                }
+            }
+
+            "commandrole" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                let command = match option(&sub.options, "command") {
+                    Some(OptionValue::String(command)) => command.trim().trim_start_matches('/'),
+                    _ => panic!("got wrong option value"),
+                };
+                if !self.commands.0.iter().any(|cmd| cmd["name"] == command) {
+                    return Err(Error::User(format!("There's no command `/{}`.", command)));
+                }
+
+                let mut roles = self
+                    .config
+                    .get(CommandPermissions)
+                    .await?
+                    .unwrap_or_default();
+                match (sub.name.as_str(), option(&sub.options, "role")) {
+                    ("set", Some(OptionValue::Role(role))) => {
+                        roles.0.insert(command.to_owned(), role.id);
+                        self.config.set(CommandPermissions, roles).await?;
+                        Ok(format!(
+                            "`/{}` can now only be used by members with {}.",
+                            command,
+                            role.id.mention()
+                        )
+                        .into())
+                    }
+
+                    ("unset", None) => {
+                        roles.0.remove(command);
+                        self.config.set(CommandPermissions, roles).await?;
+                        Ok(format!("`/{}` uses its default permissions again.", command).into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "triggeremoji" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                let action = match option(&sub.options, "action") {
+                    Some(OptionValue::String(action)) => action.as_str(),
+                    _ => panic!("got wrong option value"),
+                };
+                match (sub.name.as_str(), option(&sub.options, "emoji")) {
+                    ("set", Some(OptionValue::String(emoji))) => {
+                        let emoji = emoji.trim().to_owned();
+                        match action {
+                            "close" => self.config.set(CloseEmoji, emoji.clone()).await?,
+                            "block" => self.config.set(BlockEmoji, emoji.clone()).await?,
+                            _ => panic!("got wrong option value"),
+                        }
+                        Ok(format!(
+                            "Reacting with {} to a thread's message in the inbox will {} it.",
+                            emoji, action
+                        )
+                        .into())
+                    }
+
+                    ("unset", None) => {
+                        match action {
+                            "close" => self.config.unset(CloseEmoji).await?,
+                            "block" => self.config.unset(BlockEmoji).await?,
+                            _ => panic!("got wrong option value"),
+                        }
+                        Ok(format!("Reactions will no longer {} threads.", action).into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "history" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let user = match option(&cmd.data.options, "user") {
+                    Some(OptionValue::User(user, _)) => user,
+                    _ => panic!("got wrong option value"),
+                };
+                match option(&cmd.data.options, "transcript") {
+                    Some(OptionValue::Integer(id)) => {
+                        let transcript = match Transcript::get(&self.pool, *id).await? {
+                            Some(transcript) if transcript.user_id == user.id => transcript,
+                            _ => {
+                                return Err(Error::User(format!(
+                                    "{} has no transcript `#{}`.",
+                                    user.mention(),
+                                    id
+                                )))
+                            }
+                        };
+
+                        let content = transcript.content(&self.pool).await?;
+                        Ok(Response {
+                            content: format!(
+                                "Transcript `#{}` of `{}`, closed <t:{}:f>.",
+                                transcript.transcript_id,
+                                &transcript.codename,
+                                transcript.closed_at.timestamp()
+                            ),
+                            attachment: Some((
+                                content.into_bytes(),
+                                format!("transcript-{}.txt", transcript.codename.replace(' ', "-")),
+                            )),
+                            page: None,
+                            confirm: None,
+                        })
+                    }
+                    None => {
+                        self.show_page(Page::first(&format!("{}{}", HISTORY_QUERY, user.id)))
+                            .await
+                    }
+                    _ => panic!("got wrong option value"),
+                }
+            }
+
+            "resend" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let mut room = self.room_from_command(cmd).await?;
+                let letter = DeadLetter::latest(&self.pool, room.room_id, Direction::ToUser)
+                    .await?
+                    .ok_or_else(|| {
+                        Error::User(format!(
+                            "There's no undelivered message for `{}`.",
+                            &room.codename
+                        ))
+                    })?;
+
+                let res = async {
+                    let dm = room.user_id.create_dm_channel(ctx).await?;
+                    dm.send_message(ctx, |createmsg| {
+                        createmsg
+                            .content(&letter.content)
+                            .allowed_mentions(|mentions| mentions.empty_parse())
+                    })
+                    .await
+                }
+                .await;
+                if let Err(err) = res {
+                    ratelimit::log_failure(&ctx.http, &err).await;
+                    return Err(Error::User(format!(
+                        "Still couldn't deliver the message to `{}`: {}",
+                        &room.codename, err
+                    )));
+                }
+
+                letter.delete(&self.pool).await?;
+                room.touch(&self.pool).await?;
+                Ok(format!(
+                    "Resent the last undelivered message to `{}`.",
+                    &room.codename
+                )
+                .into())
+            }
+
+            "preview" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let raw = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::String(message) = raw {
+                    match message.as_str() {
+                        "greeting" => Ok(greeting(PREVIEW_CODENAME).into()),
+                        "close" => match self.config.get(CloseDm).await? {
+                            Some(template) => Ok(template::render(
+                                &template,
+                                &[("codename", PREVIEW_CODENAME), ("reason", PREVIEW_REASON)],
+                            )
+                            .into()),
+                            None => Ok("No close message is set, so users aren't messaged when their thread is closed.".into()),
+                        },
+                        "blocked" => Ok(BLOCKED_MESSAGE.into()),
+                        "out-of-hours" => Ok(self
+                            .config
+                            .get(OutOfHoursMessage)
+                            .await?
+                            .unwrap_or_else(|| DEFAULT_OUT_OF_HOURS_MESSAGE.into())
+                            .into()),
+                        _ => panic!("got wrong option value"),
+                    }
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "list" | "blocklist" => {
+                let query = match option(&cmd.data.options, "snoozed") {
+                    Some(OptionValue::Boolean(true)) => "snoozed",
+                    _ => &cmd.data.name,
+                };
+                if !by_role {
+                    require_list_permission(query, perms)?;
+                }
+                self.show_page(Page::first(query)).await
+            }
+
+            "priority" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let (codename, priority) = match (
+                    option(&cmd.data.options, "codename"),
+                    option(&cmd.data.options, "priority"),
+                ) {
+                    (Some(OptionValue::String(codename)), Some(OptionValue::Integer(priority))) => {
+                        (codename, *priority)
+                    }
+                    _ => panic!("got wrong option value"),
+                };
+
+                let level = usize::try_from(priority)
+                    .ok()
+                    .and_then(|i| PRIORITIES.get(i))
+                    .ok_or_else(|| Error::User(format!("Unknown priority `{}`.", priority)))?;
+
+                let mut room = self.room_from_codename(codename).await?;
+                room.set_priority(&self.pool, priority).await?;
+
+                self.rename_thread(ctx, &room).await;
+
+                Ok(format!("Set priority of `{}` to {}.", &room.codename, level).into())
+            }
+
+            "deadletters" => {
+                if !by_role && !perms.manage_channels() {
+                    return Err(Error::User(
+                        "You don't have `Manage Channels` permission.".into(),
+                    ));
+                }
+
+                let letters = DeadLetter::recent(&self.pool, DEAD_LETTER_LIST_LIMIT).await?;
+                if letters.is_empty() {
+                    return Ok("No messages failed to forward recently.".into());
+                }
+
+                Ok(letters
+                    .iter()
+                    .map(|letter| {
+                        let target = match letter.direction {
+                            Direction::ToThread => {
+                                ChannelId(letter.target_id).mention().to_string()
+                            }
+                            Direction::ToUser => UserId(letter.target_id).mention().to_string(),
+                        };
+                        let preview: String = letter.content.chars().take(100).collect();
+                        format!(
+                            "`#{}` <t:{}:R> to {}: {}\n> {}",
+                            letter.dead_letter_id,
+                            letter.created_at.timestamp(),
+                            target,
+                            letter.error,
+                            preview
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .into())
+            }
+
+            "export" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                let backup = Backup::dump(&self.pool).await?;
+                let data = serde_json::to_vec_pretty(&backup).map_err(anyhow::Error::from)?;
+                Ok(Response {
+                    content: "Exported configuration and rooms.".into(),
+                    attachment: Some((data, "modmail-backup.json".into())),
+                    page: None,
+                    confirm: None,
+                })
+            }
+
+            "dbmaintenance" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "run" => {
+                        let res = maintain(&self.pool).await?;
+                        Ok(format!(
+                            "Removed {} dead letter(s) and {} transcript(s), reclaiming {} KiB.",
+                            res.dead_letters,
+                            res.transcripts,
+                            res.reclaimed / 1024
+                        )
+                        .into())
+                    }
+
+                    "interval" => match option(&sub.options, "hours") {
+                        Some(OptionValue::Integer(hours)) => {
+                            self.config.set(MaintenanceInterval, *hours as u64).await?;
+                            Ok(format!(
+                                "Maintenance will run every {} hour(s), starting after the next one.",
+                                hours
+                            )
+                            .into())
+                        }
+                        _ => panic!("got wrong option value"),
+                    },
+
+                    "transcripts" => match option(&sub.options, "days") {
+                        Some(OptionValue::Integer(days)) => {
+                            self.config.set(TranscriptRetention, *days as u64).await?;
+                            Ok(format!(
+                                "Maintenance will delete transcripts older than {} day(s).",
+                                days
+                            )
+                            .into())
+                        }
+                        None => {
+                            self.config.unset(TranscriptRetention).await?;
+                            Ok("Transcripts will be kept forever.".into())
+                        }
+                        _ => panic!("got wrong option value"),
+                    },
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "maintenance" => {
+                if !by_role && !perms.manage_guild() {
+                    return Err(Error::User(
+                        "You don't have `Manage Server` permission.".into(),
+                    ));
+                }
+
+                let sub = cmd.data.options.first().unwrap();
+                match sub.name.as_str() {
+                    "on" => {
+                        match option(&sub.options, "message") {
+                            Some(OptionValue::String(message)) => {
+                                self.config.set(MaintenanceMessage, message.clone()).await?
+                            }
+                            None => self.config.unset(MaintenanceMessage).await?,
+                            _ => panic!("got wrong option value"),
+                        }
+                        self.config.set(MaintenanceMode, true).await?;
+                        Ok("No new threads will be opened until `/maintenance off`.".into())
+                    }
+
+                    "off" => {
+                        self.config.unset(MaintenanceMode).await?;
+                        Ok("New threads will be opened again.".into())
+                    }
+
+                    _ => Err(Error::UnknownCommand(format!(
+                        "{} {}",
+                        &cmd.data.name, &sub.name
+                    ))),
+                }
+            }
+
+            "setconfig" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                let key = match option(&cmd.data.options, "key") {
+                    Some(OptionValue::String(key)) => key.trim(),
+                    _ => panic!("got wrong option value"),
+                };
+                match option(&cmd.data.options, "value") {
+                    Some(OptionValue::String(value)) => {
+                        config::validate(key, value)
+                            .map_err(|err| Error::User(format!("Can't set config: {}.", err)))?;
+                        self.config.set_raw(key, value).await?;
+                        Ok(format!("Set `{}` to `{}`.", key, value).into())
+                    }
+                    None => {
+                        if !config::KEYS.contains(&key) {
+                            return Err(Error::User(format!("Unknown config key `{}`.", key)));
+                        }
+                        self.config.unset_raw(key).await?;
+                        Ok(format!("Unset `{}`.", key).into())
+                    }
+                    _ => panic!("got wrong option value"),
+                }
+            }
+
+            "getconfig" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                let key = match option(&cmd.data.options, "key") {
+                    Some(OptionValue::String(key)) => key.trim(),
+                    _ => panic!("got wrong option value"),
+                };
+                if !config::KEYS.contains(&key) {
+                    return Err(Error::User(format!("Unknown config key `{}`.", key)));
+                }
+                match self.config.get_raw(key).await? {
+                    Some(value) => Ok(format!("`{}` is set to `{}`.", key, value).into()),
+                    None => Ok(format!("`{}` isn't set.", key).into()),
+                }
+            }
+
+            "rebind-guild" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                let guild = match option(&cmd.data.options, "guild") {
+                    Some(OptionValue::String(id)) => {
+                        id.trim().parse::<u64>().map(GuildId).map_err(|_| {
+                            Error::User(format!("`{}` isn't a valid server ID.", id))
+                        })?
+                    }
+                    _ => panic!("got wrong option value"),
+                };
+                let old = self.guild();
+                if guild == old {
+                    return Err(Error::User("I'm already bound to that server.".into()));
+                }
+                if !ctx.cache.guilds().contains(&guild) {
+                    return Err(Error::User(format!(
+                        "I'm not a member of server `{}`, invite me first.",
+                        guild
+                    )));
+                }
+
+                self.guild.store(guild.0, Ordering::SeqCst);
+                if let Err(err) = self.register_commands(ctx).await {
+                    self.guild.store(old.0, Ordering::SeqCst);
+                    return Err(err);
+                }
+                self.config.set(Guild, guild.0).await?;
+
+                // commands left behind would only be answered with errors
+                if let Err(err) = old.set_application_commands(ctx, |cmds| cmds).await {
+                    tracing::warn!(source = ?err, guild = %old, "Failed to remove commands from old guild.");
+                }
+
+                Ok(format!(
+                    "Moved to server `{}`. Commands only work there from now on, so set up an inbox there with `/inbox set`.",
+                    guild
+                )
+                .into())
+            }
+
+            "import" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                let file = cmd.data.options.first().unwrap().resolved.as_ref().unwrap();
+                if let OptionValue::Attachment(file) = file {
+                    let data = file.download().await.map_err(anyhow::Error::from)?;
+                    let backup: Backup = serde_json::from_slice(&data)
+                        .map_err(|err| Error::User(format!("Invalid backup file: {}", err)))?;
+                    backup
+                        .validate()
+                        .map_err(|err| Error::User(format!("Invalid backup file: {}.", err)))?;
+
+                    backup.restore(&self.pool).await?;
+                    Ok("Restored configuration and rooms from backup.".into())
+                } else {
+                    panic!("got wrong option value")
+                }
+            }
+
+            "refresh-commands" => {
+                if cmd.user.id != self.owner {
+                    return Err(Error::User(
+                        "Only the bot owner can use this command.".into(),
+                    ));
+                }
+
+                self.register_commands(ctx).await?;
+                Ok("Refreshed application commands.".into())
+            }
+
+            _ => Err(Error::UnknownCommand(cmd.data.name.clone())),
+        }
+    }
+
+    /// Forwards a user's message into their room's thread.
+    async fn forward_to_thread(
+        &self,
+        ctx: &Context,
+        room: &mut Room,
+        content: &str,
+        files: &[Download],
+        reply_to: Option<&MessageLink>,
+        dm_message: MessageId,
+    ) -> Result<()> {
+        // bring the thread back if Discord archived it for inactivity
+        if room.archived {
+            room.channel_id
+                .edit_thread(ctx, |edit| edit.archived(false))
+                .await
+                .map_err(anyhow::Error::from)?;
+            room.set_archived(&self.pool, false).await?;
+
+            // the user's message matters more than the summary
+            if self.config.get(ReopenSummary).await?.unwrap_or(true) {
+                if let Err(err) = self.summarize_reopen(ctx, room).await {
+                    tracing::warn!(source = ?err, codename = %room.codename, "Failed to summarize reopened thread.");
+                }
+            }
+        }
+
+        // only ping if the thread has gone quiet, so back-and-forth doesn't spam staff
+        let ping = match self.config.get(ReplyPingRole).await? {
+            Some(role)
+                if Utc::now() - room.last_activity_at
+                    >= chrono::Duration::minutes(REPLY_PING_COOLDOWN_MINUTES) =>
+            {
+                Some(role)
+            }
+            _ => None,
+        };
+        let (ping, users) = match ping {
+            Some(role) => self.ping_targets(ctx, role).await?,
+            None => (None, Vec::new()),
+        };
+        let mentions: String = ping
+            .iter()
+            .map(|role| format!("{} ", role.mention()))
+            .chain(users.iter().map(|user| format!("{} ", user.mention())))
+            .collect();
+
+        let author = match self.config.get(ForwardStyle).await?.unwrap_or_default() {
+            MessageStyle::Plain => None,
+            MessageStyle::Embed => Some(
+                room.user_id
+                    .to_user(ctx)
+                    .await
+                    .map_err(anyhow::Error::from)?,
+            ),
+        };
+
+        let forwarded = room
+            .channel_id
+            .send_message(ctx, |createmsg| {
+                createmsg.add_files(files.iter().map(Download::as_attachment));
+                if let Some(link) = reply_to {
+                    createmsg.reference_message((room.channel_id, link.thread_message_id));
+                }
+                // remind staff their replies aren't going out yet
+                let tag = if room.pause.is_some() {
+                    PAUSE_PREFIX
+                } else {
+                    ""
+                };
+                match &author {
+                    Some(author) => {
+                        createmsg
+                            .content(format!("{}{}", mentions, tag))
+                            .embed(|emb| {
+                                emb.author(|a| a.name(author.tag()).icon_url(author.face()))
+                                    .description(content)
+                            })
+                    }
+                    None => createmsg.content(format!("{}{}{}", mentions, tag, content)),
+                }
+                .allowed_mentions(|allowed| {
+                    allowed
+                        .empty_parse()
+                        .roles(ping)
+                        .users(users.iter().copied())
+                })
+            })
+            .await;
+        let forwarded = self
+            .record_undelivered(ctx, forwarded, room, Direction::ToThread, content)
+            .await?;
+
+        MessageLink::new(&self.pool, forwarded.id, dm_message, room.room_id).await?;
+        room.touch(&self.pool).await
+    }
+
+    /// Tells staff returning to a reopened thread what they missed: when it went quiet, and
+    /// how many messages failed to be forwarded while it was archived.
+    async fn summarize_reopen(&self, ctx: &Context, room: &Room) -> Result<()> {
+        let missed = DeadLetter::count_since(
+            &self.pool,
+            room.room_id,
+            Direction::ToThread,
+            room.last_activity_at,
+        )
+        .await?;
+
+        room.channel_id
+            .send_message(ctx, |createmsg| {
+                createmsg.embed(|emb| {
+                    emb.title("Thread reopened").field(
+                        "Quiet since",
+                        format!("<t:{}:R>", room.last_activity_at.timestamp()),
+                        true,
+                    );
+                    if missed > 0 {
+                        emb.description(format!(
+                            "{} message(s) couldn't be forwarded, see `/deadletters`.",
+                            missed
+                        ));
+                    }
+                    emb
+                })
+            })
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// The full name of the room's thread, see [`thread_name`] and [`with_username`].
+    async fn thread_title(&self, ctx: &Context, room: &Room) -> Result<String> {
+        let username = if self.config.get(RevealUsername).await?.unwrap_or(false) {
+            let user = room
+                .user_id
+                .to_user(ctx)
+                .await
+                .map_err(anyhow::Error::from)?;
+            Some(user.name)
+        } else {
+            None
+        };
+        Ok(with_username(thread_name(room), username.as_deref()))
+    }
+
+    /// Brings the room's thread name in line with its state, see [`thread_name`].
+    async fn rename_thread(&self, ctx: &Context, room: &Room) {
+        let res = async {
+            let name = self.thread_title(ctx, room).await?;
+            room.channel_id
+                .edit_thread(ctx, |edit| edit.name(name))
+                .await
+                .map_err(anyhow::Error::from)?;
+            Ok::<_, Error>(())
+        }
+        .await;
+        if let Err(err) = res {
+            tracing::warn!(source = ?err, codename = %room.codename, "Failed to rename thread.");
+        }
+    }
+
+    /// Resumes forwarding to the room's user, sending staff messages queued in the meantime.
+    /// Returns how many were sent.
+    async fn resume(&self, ctx: &Context, room: &mut Room) -> Result<usize> {
+        room.set_pause(&self.pool, None).await?;
+        let mut sent = 0;
+        for id in QueuedMessage::take_all(&self.pool, room.room_id).await? {
+            // staff may have deleted the message since
+            let msg = match room.channel_id.message(ctx, id).await {
+                Ok(msg) => msg,
+                Err(_) => continue,
+            };
+
+            let content = self.thread_content(&msg);
+            let (content, files) = self.download_attachments(&msg, &content).await?;
+            let forwarded = self
+                .forward_to_user(ctx, room, &content, &files, None)
+                .await?;
+            MessageLink::new(&self.pool, msg.id, forwarded.id, room.room_id).await?;
+            sent += 1;
+        }
+
+        self.rename_thread(ctx, room).await;
+        Ok(sent)
+    }
+
+    /// Tells the room's user their thread was closed, if configured, saves a transcript,
+    /// archives the thread and forgets the room. Returns the saved transcript and its text, if
+    /// it could be fetched.
+    async fn close_room(
+        &self,
+        ctx: &Context,
+        mut room: Room,
+        reason: &str,
+    ) -> Result<Option<(Transcript, String)>> {
+        // queued messages go out before the user is told the thread is closed
+        if room.pause.is_some() {
+            self.resume(ctx, &mut room).await?;
+        }
+
+        // notify user of thread closure, if configured
+        if let Some(template) = self.config.get(CloseDm).await? {
+            let content = template::render(
+                &template,
+                &[("codename", &room.codename), ("reason", reason)],
+            );
+
+            let res = async {
+                room.user_id
+                    .create_dm_channel(ctx)
+                    .await?
+                    .send_message(ctx, |msg| msg.content(content))
+                    .await
+            }
+            .await;
+
+            if let Err(err) = res {
+                tracing::warn!(source = ?err, codename = %room.codename, "Failed to deliver close message.");
+            }
+        }
+
+        // keep a record of the conversation, without holding up the close if that fails
+        let limit = self
+            .config
+            .get(TranscriptLimit)
+            .await?
+            .unwrap_or(DEFAULT_TRANSCRIPT_LIMIT);
+        let transcript = match transcript::fetch(&ctx.http, room.channel_id, limit).await {
+            Ok((messages, truncated)) => {
+                let bot = ctx.cache.current_user_id();
+                let lines: Vec<_> = messages
+                    .iter()
+                    .map(|msg| transcript_line(msg, bot, &room.codename))
+                    .collect();
+                Some(transcript::render(&lines, truncated))
+            }
+            Err(err) => {
+                tracing::warn!(source = ?err, codename = %room.codename, "Failed to fetch transcript.");
+                None
+            }
+        };
+
+        let saved = match transcript {
+            Some(transcript) => Some((
+                Transcript::new(&self.pool, &room.codename, room.user_id, &transcript).await?,
+                transcript,
+            )),
+            None => None,
+        };
+
+        let _ = room
+            .channel_id
+            .edit_thread(ctx, |edit| edit.archived(true))
+            .await;
+
+        if self.config.get(Analytics).await?.unwrap_or(false) {
+            stats::record_close(&self.pool, &room, Utc::now()).await?;
+        }
+        let entry = format!("Closed `{}`: {}", room.codename, reason);
+        room.delete(&self.pool).await?;
+        self.presence.notify_one();
+        self.audit(ctx, Event::Close, entry).await;
+        Ok(saved)
+    }
+
+    /// Sends a staff message to the room's user, returning the DM it was sent as.
+    async fn forward_to_user(
+        &self,
+        ctx: &Context,
+        room: &mut Room,
+        content: &str,
+        files: &[Download],
+        reply_to: Option<&MessageLink>,
+    ) -> Result<Message> {
+        let forwarded = async {
+            let dm = room.user_id.create_dm_channel(ctx).await?;
+            dm.send_message(ctx, |createmsg| {
+                createmsg.add_files(files.iter().map(Download::as_attachment));
+                if let Some(link) = reply_to {
+                    createmsg.reference_message((dm.id, link.dm_message_id));
+                }
+                createmsg
+                    .content(content)
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            })
+            .await
+        }
+        .await;
+        let forwarded = self
+            .record_undelivered(ctx, forwarded, room, Direction::ToUser, content)
+            .await?;
+
+        room.touch(&self.pool).await?;
+        room.set_first_staff_reply_at(&self.pool, Utc::now())
+            .await?;
+        self.audit(
+            ctx,
+            Event::Reply,
+            format!("Replied to the user in {}.", room.channel_id.mention()),
+        )
+        .await;
+        Ok(forwarded)
+    }
+
+    /// Decides who to ping about a user's reply. Mentioning the role would reach staff who
+    /// muted notifications too, so if anyone did, the role's other members are mentioned
+    /// individually instead. Falls back to the role if its members may not all be cached, or
+    /// there are too many of them to mention.
+    async fn ping_targets(
+        &self,
+        ctx: &Context,
+        role: RoleId,
+    ) -> Result<(Option<RoleId>, Vec<UserId>)> {
+        let muted = NotificationOptout::all(&self.pool).await?;
+        if muted.is_empty() {
+            return Ok((Some(role), Vec::new()));
+        }
+
+        let guild = match ctx.cache.guild(self.guild()) {
+            // large guilds only have some of their members cached
+            Some(guild) if guild.members.len() as u64 >= guild.member_count => guild,
+            _ => {
+                tracing::info!("Guild members aren't all cached, pinging the whole role.");
+                return Ok((Some(role), Vec::new()));
+            }
+        };
+
+        let users: Vec<_> = guild
+            .members
+            .values()
+            .filter(|member| member.roles.contains(&role) && !member.user.bot)
+            .map(|member| member.user.id)
+            .filter(|user| !muted.contains(user))
+            .collect();
+        if users.len() > PING_USERS_MAX {
+            tracing::info!(
+                count = users.len(),
+                "Too many staff to mention individually, pinging the whole role."
+            );
+            return Ok((Some(role), Vec::new()));
+        }
+        Ok((None, users))
+    }
+
+    /// Holds a user's message until they stop typing for `window`, so that messages sent in
+    /// quick succession are forwarded as one.
+    async fn coalesce(
+        &self,
+        ctx: &Context,
+        mut room: Room,
+        msg: &Message,
+        content: &str,
+        window: Duration,
+    ) -> Result<Option<String>> {
+        match self.batcher.push(room.room_id, msg.id, content) {
+            Push::Started => {}
+            Push::Joined => return Ok(None),
+            Push::Overflowed(batch) => {
+                self.forward_to_thread(ctx, &mut room, &batch.content, &[], None, batch.first)
+                    .await?;
+                return Ok(None);
+            }
+        }
+
+        let mut seen = 0;
+        loop {
+            tokio::time::sleep(window).await;
+            match self.batcher.poll(room.room_id, &mut seen) {
+                Poll::Ready(batch) => {
+                    // the room may have been closed while we were waiting, so keep the batch for
+                    // staff to follow up on
+                    match Room::get(&self.pool, room.room_id).await? {
+                        Some(mut room) => {
+                            self.forward_to_thread(
+                                ctx,
+                                &mut room,
+                                &batch.content,
+                                &[],
+                                None,
+                                batch.first,
+                            )
+                            .await?;
+                        }
+                        None => {
+                            DeadLetter::new(
+                                &self.pool,
+                                None,
+                                Direction::ToThread,
+                                room.channel_id.0,
+                                batch.content,
+                                "The thread was closed while the messages were batched.".into(),
+                            )
+                            .await?;
+                        }
+                    }
+                    return Ok(None);
+                }
+                Poll::Pending => continue,
+                Poll::Gone => return Ok(None),
+            }
+        }
+    }
+
+    /// Closes or blocks a room when staff react to its thread's starter message in the inbox
+    /// with an emoji set with `/triggeremoji`, or sends the snippet set with `/snippet emoji`.
+    async fn handle_trigger_reaction(&self, ctx: &Context, reaction: &Reaction) -> Result<()> {
+        let user = match reaction.user_id {
+            Some(user) if user != ctx.cache.current_user_id() => user,
+            _ => return Ok(()),
+        };
+
+        // threads started from a message share its ID
+        let mut room = match Room::get_by_channel(&self.pool, reaction.message_id.0.into()).await? {
+            Some(room)
+                if room
+                    .inbox_id
+                    .is_none_or(|inbox| inbox == reaction.channel_id) =>
+            {
+                room
+            }
+            _ => return Ok(()),
+        };
+
+        let emoji = reaction.emoji.to_string();
+        let close = self.config.get(CloseEmoji).await?;
+        let block = self.config.get(BlockEmoji).await?;
+        if close.as_ref() == Some(&emoji) {
+            let allowed = self
+                .may_react(
+                    ctx,
+                    "close",
+                    reaction.channel_id,
+                    user,
+                    Permissions::MANAGE_CHANNELS,
+                )
+                .await?;
+            if !allowed {
+                return Ok(());
+            }
+
+            let codename = room.codename.clone();
+            let filename = format!("transcript-{}.txt", codename.replace(' ', "-"));
+            let transcript = self
+                .close_room(ctx, room, "Closed with a reaction.")
+                .await?;
+            reaction
+                .channel_id
+                .send_message(ctx, |msg| {
+                    msg.content(format!(
+                        "{} archived `{}` and removed attached user.",
+                        user.mention(),
+                        codename
+                    ))
+                    .allowed_mentions(|mentions| mentions.empty_parse());
+                    if let Some((_, transcript)) = &transcript {
+                        msg.add_file(AttachmentType::Bytes {
+                            data: transcript.as_bytes().to_vec().into(),
+                            filename,
+                        });
+                    }
+                    msg
+                })
+                .await
+                .map_err(anyhow::Error::from)?;
+        } else if block.as_ref() == Some(&emoji) {
+            let allowed = self
+                .may_react(
+                    ctx,
+                    "block",
+                    reaction.channel_id,
+                    user,
+                    Permissions::MANAGE_ROLES,
+                )
+                .await?;
+            if !allowed {
+                return Ok(());
+            }
+
+            let res = self.block_user(ctx, room.user_id).await;
+            let content = match &res {
+                Ok(()) => format!("{} blocked `{}`.", user.mention(), &room.codename),
+                Err(Error::User(err)) => err.clone(),
+                Err(_) => "Failed to block the user.".to_owned(),
+            };
+            room.channel_id
+                .send_message(ctx, |msg| {
+                    msg.content(content)
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                })
+                .await
+                .map_err(anyhow::Error::from)?;
+            res?;
+        } else if let Some(snippet) = Snippet::get_by_emoji(&self.pool, &emoji).await? {
+            let perms = self.permissions_in(ctx, reaction.channel_id, user).await?;
+            if !perms.manage_messages() || room.claimed_by.is_some_and(|staff| staff != user) {
+                return Ok(());
+            }
+            // like `/reply`, there's nothing that could be queued until `/resume`
+            if room.pause.is_some() {
+                room.channel_id
+                    .send_message(ctx, |msg| {
+                        msg.content(format!(
+                            "{} couldn't send `{}`: the thread is paused.",
+                            user.mention(),
+                            snippet.name
+                        ))
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                    })
+                    .await
+                    .map_err(anyhow::Error::from)?;
+                return Ok(());
+            }
+
+            let recipient = room
+                .user_id
+                .to_user(ctx)
+                .await
+                .map_err(anyhow::Error::from)?;
+            let content = template::render(
+                &snippet.content,
+                &[("codename", &room.codename), ("user", &recipient.name)],
+            );
+            self.forward_to_user(ctx, &mut room, &content, &[], None)
+                .await?;
+            room.channel_id
+                .send_message(ctx, |msg| {
+                    msg.content(format!(
+                        "{} sent `{}`: {}",
+                        user.mention(),
+                        snippet.name,
+                        content
+                    ))
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+                })
+                .await
+                .map_err(anyhow::Error::from)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a thread from `message` in the inbox, retrying a few times if rate limited.
+    /// Returns `None` if it still couldn't be created.
+    async fn create_thread(
+        &self,
+        ctx: &Context,
+        inbox: ChannelId,
+        message: MessageId,
+        name: &str,
+    ) -> Result<Option<GuildChannel>> {
+        let retries = self
+            .config
+            .get(ThreadCreateRetries)
+            .await?
+            .unwrap_or(DEFAULT_THREAD_CREATE_RETRIES);
+        let mut attempt = 0;
+        loop {
+            let err = match inbox
+                .create_public_thread(ctx, message, |thread| thread.name(name))
+                .await
+            {
+                Ok(thread) => return Ok(Some(thread)),
+                Err(err) => err,
+            };
+
+            let wait = ratelimit::retry_after(&ctx.http, &err, THREAD_CREATE_RETRY_FALLBACK).await;
+            match wait {
+                Some(wait) if attempt < retries && wait <= THREAD_CREATE_RETRY_MAX => {
+                    attempt += 1;
+                    tracing::warn!(attempt, retry_after = ?wait, "Rate limited creating thread, retrying.");
+                    tokio::time::sleep(wait).await;
+                }
+                Some(_) => {
+                    ratelimit::log_failure(&ctx.http, &err).await;
+                    tracing::error!(attempts = attempt + 1, "Giving up on creating thread.");
+                    return Ok(None);
+                }
+                None => return Err(anyhow::Error::from(err).into()),
+            }
+        }
+    }
+
+    /// Lets staff know when a user reacts to one of the messages forwarded to them.
+    async fn handle_reaction(&self, ctx: &Context, reaction: &Reaction) -> Result<()> {
+        if reaction.guild_id == Some(self.guild()) {
+            return self.handle_trigger_reaction(ctx, reaction).await;
+        }
+
+        let user = match reaction.user_id {
+            Some(user) if reaction.guild_id.is_none() && user != ctx.cache.current_user_id() => {
+                user
+            }
+            _ => return Ok(()),
+        };
+
+        let link = match MessageLink::get_by_dm_message(&self.pool, reaction.message_id).await? {
+            Some(link) => link,
+            None => return Ok(()),
+        };
+        let room = match Room::get(&self.pool, link.room_id).await? {
+            Some(room) if room.user_id == user => room,
+            _ => return Ok(()),
+        };
+
+        // links also cover the user's own messages, which aren't worth reporting
+        let dm = reaction.message(ctx).await.map_err(anyhow::Error::from)?;
+        if dm.author.id != ctx.cache.current_user_id() {
+            return Ok(());
+        }
+
+        let emoji = reaction.emoji.to_string();
+        if !self
+            .reactions
+            .allow(reaction.message_id, &emoji, Utc::now())
+        {
+            return Ok(());
+        }
+
+        room.channel_id
+            .send_message(ctx, |msg| {
+                msg.content(format!("User reacted {} to this message.", emoji))
+                    .reference_message((room.channel_id, link.thread_message_id))
+                    .allowed_mentions(|mentions| mentions.empty_parse().replied_user(false))
+            })
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Mirrors edits of staff replies onto the copies sent to users.
+    async fn handle_edit(&self, ctx: &Context, event: &MessageUpdateEvent) -> Result<()> {
+        let (content, author) = match (&event.content, &event.author) {
+            (Some(content), Some(author)) if !author.bot => (content, author),
+            _ => return Ok(()),
+        };
+
+        if event.guild_id.is_none() {
+            return self.handle_dm_edit(ctx, event, content, author.id).await;
+        }
+
+        // edits would arrive without content, blanking the user's copy
+        if !self.message_content {
+            return Ok(());
+        }
+
+        let room = match Room::get_by_channel(&self.pool, event.channel_id).await? {
+            Some(room) => room,
+            None => return Ok(()),
+        };
+
+        // only messages that were forwarded have a link
+        let link = match MessageLink::get_by_thread_message(&self.pool, event.id).await? {
+            Some(link) => link,
+            None => return Ok(()),
+        };
+
+        if !self.settle_edit(event.id).await? {
+            return Ok(());
+        }
+
+        let content = with_edited_marker(&MessageBuilder::new().push_safe(content).build());
+        let mut forwarded = room
+            .user_id
+            .create_dm_channel(ctx)
+            .await
+            .map_err(anyhow::Error::from)?
+            .message(ctx, link.dm_message_id)
+            .await
+            .map_err(anyhow::Error::from)?;
+        // keep the notes on blocked attachments that were forwarded along with the reply
+        let (_, notes) = attachments::split_notes(&forwarded.content);
+        let content = attachments::with_notes(&content, &notes);
+        forwarded
+            .edit(ctx, |edit| edit.content(content))
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Mirrors a user's edit of a DM in the copy forwarded to their thread.
+    async fn handle_dm_edit(
+        &self,
+        ctx: &Context,
+        event: &MessageUpdateEvent,
+        content: &str,
+        user: UserId,
+    ) -> Result<()> {
+        // a forwarded message may combine several, which one edit can't account for
+        if self.config.get(CoalesceWindow).await?.is_some() {
+            return Ok(());
+        }
+
+        let link = match MessageLink::get_by_dm_message(&self.pool, event.id).await? {
+            Some(link) => link,
+            None => return Ok(()),
+        };
+        let room = match Room::get(&self.pool, link.room_id).await? {
+            Some(room) if room.user_id == user => room,
+            _ => return Ok(()),
+        };
+
+        if !self.settle_edit(event.id).await? {
+            return Ok(());
+        }
+
+        let content = with_edited_marker(&filter::sanitize(content, &self.filters().await?));
+        let mut forwarded = room
+            .channel_id
+            .message(ctx, link.thread_message_id)
+            .await
+            .map_err(anyhow::Error::from)?;
+        // keep what was forwarded along with the user's message, like notes on blocked
+        // attachments; a thread's first message and embed-styled ones carry it in an embed
+        match forwarded.embeds.first().cloned() {
+            Some(embed) => {
+                let (_, notes) =
+                    attachments::split_notes(embed.description.as_deref().unwrap_or_default());
+                let mut embed = CreateEmbed::from(embed);
+                embed.description(attachments::with_notes(&content, &notes));
+                forwarded.edit(ctx, |edit| edit.set_embed(embed)).await
+            }
+            None => {
+                let (prefix, message) = split_forward_prefix(&forwarded.content);
+                let (_, notes) = attachments::split_notes(message);
+                let content = format!("{}{}", prefix, attachments::with_notes(&content, &notes));
+                forwarded.edit(ctx, |edit| edit.content(content)).await
+            }
+        }
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Waits out a burst of edits of `message`, returning whether this edit was the last one
+    /// and should be forwarded.
+    async fn settle_edit(&self, message: MessageId) -> Result<bool> {
+        let window = self
+            .config
+            .get(EditDebounce)
+            .await?
+            .unwrap_or(DEFAULT_EDIT_DEBOUNCE_MS)
+            .min(EDIT_DEBOUNCE_MAX_MS);
+        let edit = self.edits.push(message);
+        tokio::time::sleep(Duration::from_millis(window)).await;
+        Ok(self.edits.settle(message, edit))
+    }
+
+    /// Forwards a user's DM into their room's thread.
+    async fn forward_dm(
+        &self,
+        ctx: &Context,
+        mut room: Room,
+        msg: &Message,
+    ) -> Result<Option<String>> {
+        let reply_to = match &msg.referenced_message {
+            Some(referenced) => MessageLink::get_by_dm_message(&self.pool, referenced.id).await?,
+            None => None,
+        };
+
+        let content = filter::sanitize(dm_content(msg), &self.filters().await?);
+        if let Some(window) = self.config.get(CoalesceWindow).await? {
+            if msg.attachments.is_empty() && msg.referenced_message.is_none() {
+                let window = Duration::from_millis(window);
+                return self.coalesce(ctx, room, msg, &content, window).await;
+            }
+
+            // keep anything still batched ahead of this message
+            if let Some(batch) = self.batcher.flush(room.room_id) {
+                self.forward_to_thread(ctx, &mut room, &batch.content, &[], None, batch.first)
+                    .await?;
+            }
+        }
+
+        let (content, files) = self.download_attachments(msg, &content).await?;
+        self.forward_to_thread(ctx, &mut room, &content, &files, reply_to.as_ref(), msg.id)
+            .await?;
+        Ok(None)
+    }
+
+    /// Picks which of the user's rooms a DM goes to while `categories` are set up: the room of
+    /// the message it replies to, or the user's only room. Returns `None` if the user has to
+    /// pick, e.g. because they have no room yet or asked for a new one.
+    async fn route_dm(&self, msg: &Message) -> Result<Option<Room>> {
+        if dm_commands::split(&msg.content, NEW_THREAD_COMMAND).is_some() {
+            return Ok(None);
+        }
+
+        let mut rooms = Room::for_user(&self.pool, msg.author.id).await?;
+        if let Some(referenced) = &msg.referenced_message {
+            if let Some(link) = MessageLink::get_by_dm_message(&self.pool, referenced.id).await? {
+                if let Some(i) = rooms.iter().position(|room| room.room_id == link.room_id) {
+                    return Ok(Some(rooms.swap_remove(i)));
+                }
+            }
+        }
+
+        Ok(match rooms.len() {
+            1 => rooms.pop(),
+            _ => None,
+        })
+    }
+
+    /// Asks the author of a DM which category it's about, with buttons for the categories they
+    /// can open a room in, or for their rooms if they have several.
+    async fn ask_category(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        categories: &categories::Categories,
+    ) -> Result<Option<String>> {
+        let rooms = Room::for_user(&self.pool, msg.author.id).await?;
+        let new = dm_commands::split(&msg.content, NEW_THREAD_COMMAND).is_some();
+        let (prompt, options): (_, Vec<_>) = if rooms.len() > 1 && !new {
+            (
+                ROOM_PROMPT,
+                rooms.into_iter().map(|room| room.category).collect(),
+            )
+        } else {
+            (
+                CATEGORY_PROMPT,
+                categories
+                    .0
+                    .iter()
+                    .filter(|category| {
+                        !rooms
+                            .iter()
+                            .any(|room| room.category.as_ref() == Some(*category))
+                    })
+                    .cloned()
+                    .map(Some)
+                    .collect(),
+            )
+        };
+        if options.is_empty() {
+            return Ok(Some(
+                "You already have a thread in every category, reply to a message in one to \
+                write there."
+                    .into(),
+            ));
+        }
+
+        let show_footer = self.show_footer().await;
+        msg.channel_id
+            .send_message(ctx, |send| {
+                send.reference_message(msg)
+                    .embed(|emb| {
+                        if show_footer {
+                            emb.footer(|foot| foot.text(FOOTER));
+                        }
+                        emb.color(Color::BLURPLE).description(prompt)
+                    })
+                    .components(|c| Choice::buttons(&options, msg.id, c))
+            })
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(None)
+    }
+
+    /// Forwards a DM held back by [`Bot::ask_category`] once the user picked where it goes,
+    /// opening a room in the category if they have none there yet.
+    async fn choose_category(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        choice: Choice,
+    ) -> Result<Response> {
+        let msg = component
+            .channel_id
+            .message(ctx, choice.message)
+            .await
+            .map_err(anyhow::Error::from)?;
+        if msg.author.id != component.user.id {
+            return Err(Error::User("These buttons aren't for you.".into()));
+        }
+        if MessageLink::get_by_dm_message(&self.pool, msg.id)
+            .await?
+            .is_some()
+        {
+            return Err(Error::User("This message was sent already.".into()));
+        }
+        if self.blocked(ctx, &msg.author).await? {
+            return Err(Error::User(BLOCKED_MESSAGE.into()));
+        }
+
+        let room =
+            Room::get_by_user_and_category(&self.pool, msg.author.id, choice.category.as_deref())
+                .await?;
+        let reply = match room {
+            Some(room) => self
+                .forward_dm(ctx, room, &msg)
+                .await?
+                .unwrap_or_else(|| "Sent your message.".into()),
+            None => self
+                .open_room(ctx, &msg, choice.category)
+                .await?
+                .unwrap_or_else(|| "Your message couldn't be sent.".into()),
+        };
+        Ok(reply.into())
+    }
+
+    /// Opens a room in `category` for the author of a DM, forwarding the DM as its first
+    /// message.
+    async fn open_room(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        category: Option<String>,
+    ) -> Result<Option<String>> {
+        let inbox = match self.config.get(Inbox).await? {
+            Some(inbox) => inbox,
+            None => return Ok(None),
+        };
+
+        if self.config.get(MaintenanceMode).await?.unwrap_or(false) {
+            let notice = self
+                .config
+                .get(MaintenanceMessage)
+                .await?
+                .unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.into());
+            return Ok(Some(notice));
+        }
+
+        let out_of_hours = match self.config.get(OfficeHours).await? {
+            Some(schedule) if !schedule.contains(Utc::now()) => Some(
+                self.config
+                    .get(OutOfHoursMessage)
+                    .await?
+                    .unwrap_or_else(|| DEFAULT_OUT_OF_HOURS_MESSAGE.into()),
+            ),
+            _ => None,
+        };
+
+        if let Some(notice) = &out_of_hours {
+            if self.config.get(OutOfHoursHold).await?.unwrap_or(false) {
+                return Ok(Some(notice.clone()));
+            }
+        }
+
+        // once the inbox is full, new threads go to the overflow inbox or are refused
+        let inbox = match self.config.get(MaxOpenThreads).await? {
+            Some(max) if Room::count_in_inbox(&self.pool, inbox).await? >= max => {
+                match self.config.get(OverflowInbox).await? {
+                    Some(overflow) => overflow,
+                    None => return Ok(Some(INBOX_FULL_MESSAGE.into())),
+                }
+            }
+            _ => inbox,
+        };
+
+        // let users pick their own codename, if allowed
+        let mut body = dm_content(msg);
+        if let Some(("", rest)) = dm_commands::split(body, NEW_THREAD_COMMAND) {
+            body = rest;
+        }
+        let mut requested = None;
+        if let Some(command) = self.config.get(CodenameCommand).await? {
+            if let Some((name, rest)) = dm_commands::split(body, &command) {
+                requested = Some(codenames::sanitize(name));
+                body = rest;
+            }
+        }
+
+        let mut rejection = None;
+        let requested = match requested {
+            Some(name)
+                if !(codenames::MIN_LEN..=codenames::MAX_LEN).contains(&name.chars().count()) =>
+            {
+                rejection = Some(format!(
+                    "Codenames must be between {} and {} characters long",
+                    codenames::MIN_LEN,
+                    codenames::MAX_LEN
+                ));
+                None
+            }
+            Some(name)
+                if self.codename_taken(&name, msg.author.id).await?
+                    || Room::codename_exists(&self.pool, &name).await? =>
+            {
+                rejection = Some(format!("The codename `{}` is already taken", name));
+                None
+            }
+            requested => requested,
+        };
+
+        // a codename given by staff sticks, as long as nobody else's room has it
+        let alias = match Alias::get(&self.pool, msg.author.id).await? {
+            Some(alias) if !Room::codename_exists(&self.pool, &alias).await? => {
+                rejection = None;
+                Some(alias)
+            }
+            _ => None,
+        };
+
+        let codename = match alias.or(requested) {
+            Some(name) => name,
+            None => self.fresh_codename().await?,
+        };
+
+        let thread = {
+            let inbox_msg = inbox
+                .send_message(ctx, |f| {
+                    f.content("New thread created.").embed(|e| {
+                        e.title("New Modmail Received")
+                            .field(
+                                "Creator",
+                                format!("{}, ({})", &msg.author.mention(), &msg.author.tag()),
+                                true,
+                            )
+                            .field("Codename", &codename, true);
+                        if let Some(category) = &category {
+                            e.field("Category", category, true);
+                        }
+                        if out_of_hours.is_some() {
+                            e.field("Note", "Received outside of office hours.", false);
+                        }
+                        e
+                    })
+                })
+                .await
+                .map_err(anyhow::Error::from)?;
+
+            let reveal = self.config.get(RevealUsername).await?.unwrap_or(false);
+            let name = with_username(codename.clone(), reveal.then_some(msg.author.name.as_str()));
+            match self.create_thread(ctx, inbox, inbox_msg.id, &name).await? {
+                Some(thread) => thread,
+                None => {
+                    // don't leave the announcement of a thread that never came to be
+                    let _ = inbox_msg.delete(ctx).await;
+                    return Ok(Some(THREAD_BUSY_MESSAGE.into()));
+                }
+            }
+        };
+
+        let content = filter::sanitize(body, &self.filters().await?);
+        let (content, files) = self.download_attachments(msg, &content).await?;
+        let forwarded = thread
+            .send_message(ctx, |createmsg| {
+                createmsg
+                    .add_files(files.iter().map(Download::as_attachment))
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+                    .embed(|f| {
+                        f.author(|a| a.name(msg.author.tag()).icon_url(msg.author.face()))
+                            .description(&content)
+                    })
+            })
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        let mut room =
+            Room::with_category(&self.pool, codename, thread.id, msg.author.id, category).await?;
+        self.presence.notify_one();
+        self.audit(
+            ctx,
+            Event::Open,
+            format!(
+                "Opened {} for {} as `{}`.",
+                thread.id.mention(),
+                msg.author.mention(),
+                &room.codename
+            ),
+        )
+        .await;
+        room.set_inbox_id(&self.pool, inbox).await?;
+        MessageLink::new(&self.pool, forwarded.id, msg.id, room.room_id).await?;
+
+        let mut reply = match rejection {
+            Some(rejection) => format!(
+                "{}, so you've been assigned the codename `{}` instead.",
+                rejection, &room.codename
+            ),
+            None => greeting(&room.codename),
+        };
+        if let Some(notice) = out_of_hours {
+            reply.push_str("\n\n");
+            reply.push_str(&notice);
+        }
+        Ok(Some(reply))
+    }
+
+    async fn handle_message(&self, ctx: &Context, msg: &Message) -> Result<Option<String>> {
+        if msg.author.id == ctx.cache.current_user().id {
+            return Ok(None);
+        }
+
+        if msg.is_private() {
+            // other bots could otherwise open junk threads or reply to the bot in a loop
+            if (msg.author.bot || msg.webhook_id.is_some())
+                && self.config.get(IgnoreBots).await?.unwrap_or(true)
+            {
+                return Ok(None);
+            }
+
+            let blocked = self.blocked(ctx, &msg.author).await?;
+
+            // blocked users may still ask, so they know where they stand
+            if dm_commands::split(&msg.content, WHOAMI_COMMAND).is_some() {
+                let rooms = Room::for_user(&self.pool, msg.author.id).await?;
+                let mut reply = match rooms.as_slice() {
+                    [] => "You don't have an open thread. Send a message to start one.".into(),
+                    [room] => format!("Your thread's codename is `{}`.", room.codename),
+                    rooms => {
+                        let threads: Vec<_> = rooms
+                            .iter()
+                            .map(|room| {
+                                format!(
+                                    "`{}` ({})",
+                                    room.codename,
+                                    room.category.as_deref().unwrap_or(UNCATEGORIZED_LABEL)
+                                )
+                            })
+                            .collect();
+                        format!("Your threads' codenames are {}.", threads.join(", "))
+                    }
+                };
+                if blocked {
+                    reply.push_str("\n\n");
+                    reply.push_str(BLOCKED_MESSAGE);
+                }
+                return Ok(Some(reply));
+            }
+
+            if blocked {
+                return Ok(Some(BLOCKED_MESSAGE.into()));
+            }
+
+            let room = match self.config.get(Categories).await? {
+                Some(categories) if !categories.0.is_empty() => match self.route_dm(msg).await? {
+                    Some(room) => room,
+                    None => return self.ask_category(ctx, msg, &categories).await,
+                },
+                _ => match Room::get_by_user(&self.pool, msg.author.id).await? {
+                    Some(room) => room,
+                    None => return self.open_room(ctx, msg, None).await,
+                },
+            };
+            self.forward_dm(ctx, room, msg).await
+        } else {
+            let mut room = match Room::get_by_channel(&self.pool, msg.channel_id).await? {
+                Some(room) => room,
+                None => return Ok(None),
+            };
+
+            if let Some(staff) = room.claimed_by {
+                if staff != msg.author.id {
+                    return Ok(Some(format!(
+                        "This thread is claimed by {}, so your message wasn't forwarded.",
+                        staff.mention()
+                    )));
+                }
+            }
+
+            match room.pause {
+                Some(Pause::Queue) => {
+                    QueuedMessage::add(&self.pool, room.room_id, msg.id).await?;
+                    return Ok(Some(
+                        "Forwarding is paused, so your message will be sent on `/resume`.".into(),
+                    ));
+                }
+                Some(Pause::Drop) => {
+                    return Ok(Some(
+                        "Forwarding is paused, so your message won't be sent.".into(),
+                    ));
+                }
+                None => {}
+            }
+
+            // preserve reply threading on the user's side, if we know the original DM
+            let reply_to = match &msg.referenced_message {
+                Some(referenced) => {
+                    MessageLink::get_by_thread_message(&self.pool, referenced.id).await?
+                }
+                None => None,
+            };
+
+            let content = self.thread_content(msg);
+            let (content, files) = self.download_attachments(msg, &content).await?;
+            let forwarded = self
+                .forward_to_user(ctx, &mut room, &content, &files, reply_to.as_ref())
+                .await?;
+
+            MessageLink::new(&self.pool, msg.id, forwarded.id, room.room_id).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Posts reminders set with `/remind` once they're due, until the bot shuts down.
+async fn deliver_reminders(pool: SqlitePool, http: Arc<Http>) {
+    let mut interval = tokio::time::interval(REMINDER_INTERVAL);
+    loop {
+        interval.tick().await;
+        let due = match Reminder::due(&pool, Utc::now()).await {
+            Ok(due) => due,
+            Err(err) => {
+                tracing::error!(source = ?err, "Error while fetching reminders.");
+                continue;
+            }
+        };
+
+        for reminder in due {
+            let res = reminder
+                .channel_id
+                .send_message(&http, |msg| {
+                    msg.content(format!(
+                        "{} Reminder: follow up on this thread.",
+                        reminder.user_id.mention()
+                    ))
+                    .allowed_mentions(|mentions| mentions.empty_parse().users([reminder.user_id]))
+                })
+                .await;
+
+            if let Err(err) = res {
+                tracing::warn!(source = ?err, channel = %reminder.channel_id, "Failed to deliver reminder.");
+            }
+
+            // failed reminders are dropped too, the thread is most likely gone
+            if let Err(err) = reminder.delete(&pool).await {
+                tracing::error!(source = ?err, "Error while deleting reminder.");
+            }
+        }
+    }
+}
+
+/// Takes the block role from `members`, giving back the roles taken by `/blockstrip`. This can
+/// outlast the interaction that started it, so progress is reported in `channel`. Clears
+/// `running` once done.
+async fn unblock_members(
+    pool: SqlitePool,
+    http: Arc<Http>,
+    role: RoleId,
+    members: Vec<Member>,
+    channel: ChannelId,
+    running: Arc<AtomicBool>,
+) {
+    let total = members.len();
+    let res = async {
+        let (mut unblocked, mut failed) = (0, 0);
+        for (done, mut member) in members.into_iter().enumerate() {
+            if done > 0 && done % UNBLOCK_PROGRESS_INTERVAL == 0 {
+                let progress = format!("Unblocked {} of {} member(s) so far.", unblocked, total);
+                if let Err(err) = channel.say(&http, progress).await {
+                    tracing::warn!(source = ?err, "Failed to report unblocking progress.");
+                }
+            }
+
+            let stripped = Block::stripped_roles(&pool, member.user.id).await?;
+            let res = async {
+                member.remove_role(&http, role).await?;
+                if !stripped.is_empty() {
+                    member.add_roles(&http, &stripped).await?;
+                }
+                Ok::<_, serenity::Error>(())
+            }
+            .await;
+
+            match res {
+                Ok(()) => {
+                    Block::remove(&pool, member.user.id).await?;
+                    unblocked += 1;
+                }
+                Err(err) => {
+                    ratelimit::log_failure(&http, &err).await;
+                    failed += 1;
+                }
+            }
+            tokio::time::sleep(UNBLOCK_DELAY).await;
+        }
+        Ok::<_, Error>((unblocked, failed))
+    }
+    .await;
+    running.store(false, Ordering::SeqCst);
+
+    let reply = match res {
+        Ok((unblocked, failed)) => {
+            let mut reply = format!("Unblocked {} member(s).", unblocked);
+            if failed > 0 {
+                reply.push_str(&format!(
+                    " {} member(s) couldn't be unblocked, check my permissions.",
+                    failed
+                ));
+            }
+            reply
+        }
+        Err(err) => {
+            tracing::error!(source = ?err, "Error while unblocking members.");
+            "Stopped unblocking members after an error.".to_owned()
+        }
+    };
+    if let Err(err) = channel.say(&http, reply).await {
+        tracing::warn!(source = ?err, "Failed to report unblocking results.");
+    }
+}
+
+/// Sends `content` to each user and copies it into each of their rooms' threads. This can
+/// outlast the interaction that started it, so progress is reported in `channel`. Clears
+/// `running` once done.
+async fn broadcast(
+    http: Arc<Http>,
+    content: String,
+    users: Vec<(UserId, Vec<Room>)>,
+    channel: ChannelId,
+    running: Arc<AtomicBool>,
+) {
+    let total = users.len();
+    let (mut undelivered, mut failed) = (0, Vec::new());
+    for (done, (user, rooms)) in users.into_iter().enumerate() {
+        if done > 0 && done % BROADCAST_PROGRESS_INTERVAL == 0 {
+            let progress = format!("Broadcast sent to {} of {} user(s) so far.", done, total);
+            if let Err(err) = channel.say(&http, progress).await {
+                tracing::warn!(source = ?err, "Failed to report broadcast progress.");
+            }
+        }
+
+        let res = async {
+            user.create_dm_channel(&http)
+                .await?
+                .send_message(&http, |msg| msg.content(&content))
+                .await?;
+
+            for room in &rooms {
+                room.channel_id
+                    .send_message(&http, |msg| {
+                        msg.embed(|emb| {
+                            emb.title("Broadcast sent")
+                                .description(&content)
+                                .color(Color::BLURPLE)
+                        })
+                    })
+                    .await?;
+            }
+            Ok::<_, serenity::Error>(())
+        }
+        .await;
+
+        if let Err(err) = res {
+            tracing::warn!(source = ?err, user = %user, "Failed to deliver broadcast.");
+            undelivered += 1;
+            failed.extend(rooms.into_iter().map(|room| format!("`{}`", room.codename)));
+        }
+
+        tokio::time::sleep(BROADCAST_DELAY).await;
+    }
+    running.store(false, Ordering::SeqCst);
+
+    let mut reply = format!(
+        "Broadcast delivered to {} of {} users.",
+        total - undelivered,
+        total
+    );
+    if !failed.is_empty() {
+        reply.push_str(&format!("\nFailed: {}", failed.join(", ")));
+    }
+    if let Err(err) = channel.say(&http, reply).await {
+        tracing::warn!(source = ?err, "Failed to report broadcast results.");
+    }
+}
+
+/// Sends messages scheduled with `/schedule` once they're due, until the bot shuts down.
+async fn deliver_scheduled(pool: SqlitePool, http: Arc<Http>) {
+    let mut interval = tokio::time::interval(REMINDER_INTERVAL);
+    loop {
+        interval.tick().await;
+        let due = match ScheduledMessage::due(&pool, Utc::now()).await {
+            Ok(due) => due,
+            Err(err) => {
+                tracing::error!(source = ?err, "Error while fetching scheduled messages.");
+                continue;
+            }
+        };
+
+        for scheduled in due {
+            let id = scheduled.scheduled_id;
+            if let Err(err) = send_scheduled(&pool, &http, scheduled).await {
+                tracing::error!(source = ?err, scheduled = id, "Error while sending scheduled message.");
+            }
+        }
+    }
+}
+
+/// Pings the reply ping role in threads staff haven't replied in within `sla_minutes`, once per
+/// thread, until the bot shuts down. Snoozed threads wait until they wake up.
+async fn alert_unanswered(pool: SqlitePool, http: Arc<Http>) {
+    let config = Config::new(pool.clone());
+    let mut interval = tokio::time::interval(REMINDER_INTERVAL);
+    loop {
+        interval.tick().await;
+        let res = async {
+            let minutes = match config.get(SlaMinutes).await? {
+                // only values written to the database directly can be out of range
+                Some(minutes) => minutes.clamp(1, SLA_MINUTES_MAX) as i64,
+                None => return Ok(()),
+            };
+            let role = config.get(ReplyPingRole).await?;
+
+            let now = Utc::now();
+            let opened_before = now - chrono::Duration::minutes(minutes);
+            for mut room in Room::unanswered(&pool, opened_before).await? {
+                if room.snoozed(now) {
+                    continue;
+                }
+
+                let waited = room.opened_at.map_or(minutes, |opened_at| {
+                    (now - opened_at).num_minutes()
+                });
+                let res = room
+                    .channel_id
+                    .send_message(&http, |msg| {
+                        let mention = role.map(|role| format!("{} ", role.mention()));
+                        msg.content(format!(
+                            "{}Thread {} has gone unanswered for {} minutes.",
+                            mention.unwrap_or_default(),
+                            room.codename,
+                            waited
+                        ))
+                        .allowed_mentions(|mentions| mentions.empty_parse().roles(role))
+                    })
+                    .await;
+                if let Err(err) = res {
+                    tracing::warn!(source = ?err, codename = %room.codename, "Failed to send SLA alert.");
+                }
+
+                // failed alerts are dropped too, so a missing thread doesn't get retried forever
+                room.set_sla_alerted(&pool).await?;
+            }
+            Ok::<_, Error>(())
+        }
+        .await;
 
-    async fn room_from_user(&self, user_id: u64) -> Result<Option<Room>> {
-        // HACK: query!() drops temporaries for some reason, must pass reference
-        let temp = &user_id.to_string();
-        Ok(
-            sqlx::query_as!(RawRoom, "SELECT * FROM rooms WHERE user_id = ?", temp)
-                .fetch_optional(&self.pool)
-                .await
-                .map_err(anyhow::Error::from)?
-                .map(|rt| Room::try_from(rt).expect("got malformed Room object from database")),
-        )
+        if let Err(err) = res {
+            tracing::error!(source = ?err, "Error while checking unanswered threads.");
+        }
     }
+}
 
-    async fn delete_room(&self, room_id: i64) -> Result<()> {
-        sqlx::query!("DELETE FROM rooms WHERE room_id = ?", room_id)
-            .execute(&self.pool)
-            .await
-            .map_err(anyhow::Error::from)?;
-        Ok(())
-    }
+/// Sends a scheduled message to its user and posts a copy in their thread. Messages for paused
+/// threads wait until the thread is resumed.
+async fn send_scheduled(pool: &SqlitePool, http: &Http, scheduled: ScheduledMessage) -> Result<()> {
+    let mut room = match Room::get(pool, scheduled.room_id).await? {
+        Some(room) if room.pause.is_some() => return Ok(()),
+        Some(room) => room,
+        None => return scheduled.delete(pool).await,
+    };
 
-    async fn new_room(&self, codename: &str, channel_id: u64, user_id: u64) -> Result<()> {
-        // HACK: query!() drops temporaries for some reason, must pass reference
-        let (channel_id, user_id) = (&channel_id.to_string(), &user_id.to_string());
-        let res = sqlx::query!(
-            "INSERT INTO rooms (codename, channel_id, user_id) VALUES (?, ?, ?)",
-            codename,
-            channel_id,
-            user_id
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(anyhow::Error::from)?;
+    // forget the message first, so a failure further down can't send it twice
+    let (id, author, content) = (
+        scheduled.scheduled_id,
+        scheduled.author_id,
+        scheduled.content.clone(),
+    );
+    scheduled.delete(pool).await?;
 
-        assert_eq!(res.rows_affected(), 1);
-        Ok(())
+    let sent = async {
+        let dm = room.user_id.create_dm_channel(http).await?;
+        dm.send_message(http, |msg| {
+            msg.content(&content)
+                .allowed_mentions(|mentions| mentions.empty_parse())
+        })
+        .await
     }
+    .await;
 
-    async fn check_codename_exists(&self, codename: &str) -> Result<bool> {
-        // HACK: macro doesn't work, treats EXISTS() as a column name
-        let (exists,): (bool,) =
-            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM rooms WHERE codename = ?)")
-                .bind(codename)
-                .fetch_one(&self.pool)
+    match sent {
+        Ok(dm_message) => {
+            let copy = room
+                .channel_id
+                .send_message(http, |msg| {
+                    msg.content(format!(
+                        "Scheduled message `#{}` from {}:\n{}",
+                        id,
+                        author.mention(),
+                        &content
+                    ))
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+                })
                 .await
                 .map_err(anyhow::Error::from)?;
-        Ok(exists)
+            MessageLink::new(pool, copy.id, dm_message.id, room.room_id).await?;
+            room.touch(pool).await?;
+        }
+        Err(err) => {
+            DeadLetter::new(
+                pool,
+                Some(room.room_id),
+                Direction::ToUser,
+                room.user_id.0,
+                content,
+                err.to_string(),
+            )
+            .await?;
+            room.channel_id
+                .say(
+                    http,
+                    format!("Scheduled message `#{}` couldn't be delivered: {}", id, err),
+                )
+                .await
+                .map_err(anyhow::Error::from)?;
+        }
     }
+    Ok(())
+}
 
-    async fn execute_command(
-        &self,
-        ctx: &Context,
-        cmd: &ApplicationCommandInteraction,
-    ) -> Result<String> {
-        let perms = cmd.member.as_ref().unwrap().permissions.unwrap();
-        match cmd.data.name.as_str() {
-            "blockrole" => {
-                if !perms.manage_roles() {
-                    return Err(Error::User(
-                        "You don't have `Manage Roles` permission.".into(),
-                    ));
-                }
-
-                let sub = cmd.data.options.get(0).unwrap();
-                match sub.name.as_str() {
-                    "set" => {
-                        let role = sub.options.get(0).unwrap().resolved.as_ref().unwrap();
-                        if let OptionValue::Role(role) = role {
-                            self.set_blockrole(role).await?;
-                            Ok(format!("Set block role to `{}`.", role.name.as_str()))
-                        } else {
-                            panic!("got wrong option value")
-                        }
-                    }
+/// Purges old dead letters and transcripts and compacts the database, logging what was freed.
+async fn maintain(pool: &SqlitePool) -> Result<Maintenance> {
+    let retention = Config::new(pool.clone())
+        .get(TranscriptRetention)
+        .await?
+        .map(|days| chrono::Duration::days(days as i64));
+    let res = Maintenance::run(
+        pool,
+        chrono::Duration::days(DEAD_LETTER_RETENTION_DAYS),
+        retention,
+    )
+    .await?;
 
-                    "unset" => {
-                        self.unset_blockrole().await?;
-                        Ok("Unset block role.".into())
-                    }
+    tracing::info!(
+        dead_letters = res.dead_letters,
+        transcripts = res.transcripts,
+        reclaimed_bytes = res.reclaimed,
+        "Finished database maintenance."
+    );
+    Ok(res)
+}
 
-                    _ => Err(Error::UnknownCommand(format!(
-                        "{} {}",
-                        &cmd.data.name, &sub.name
-                    ))),
-                }
+/// Runs [`maintain`] every `/dbmaintenance interval` hours, until the bot shuts down. The first
+/// run waits a full interval, so restarts don't trigger it at a busy time.
+async fn run_maintenance(pool: SqlitePool) {
+    let config = Config::new(pool.clone());
+    loop {
+        let hours = match config.get(MaintenanceInterval).await {
+            Ok(hours) => hours.unwrap_or(DEFAULT_MAINTENANCE_INTERVAL_HOURS),
+            Err(err) => {
+                tracing::error!(source = ?err, "Error while fetching maintenance interval.");
+                DEFAULT_MAINTENANCE_INTERVAL_HOURS
             }
+        };
+        // only values written to the database directly can be out of range
+        let hours = hours.clamp(1, MAINTENANCE_INTERVAL_MAX);
+        tokio::time::sleep(Duration::from_secs(hours.saturating_mul(3600))).await;
 
-            "inbox" => {
-                if !perms.manage_channels() {
-                    return Err(Error::User(
-                        "You don't have `Manage Channels` permission.".into(),
-                    ));
-                }
-
-                let sub = cmd.data.options.get(0).unwrap();
-                match sub.name.as_str() {
-                    "set" => {
-                        let raw = sub.options.get(0).unwrap().resolved.as_ref().unwrap();
-                        if let OptionValue::Channel(channel) = raw {
-                            self.set_inbox(channel).await?;
-                            Ok(format!("Set inbox to {}.", channel.id.mention()))
-                        } else {
-                            panic!("got wrong option value")
-                        }
-                    }
-
-                    "unset" => {
-                        self.unset_inbox().await?;
-                        Ok("Unset inbox.".into())
-                    }
+        if let Err(err) = maintain(&pool).await {
+            tracing::error!(source = ?err, "Error while maintaining database.");
+        }
+    }
+}
 
-                    _ => Err(Error::UnknownCommand(format!(
-                        "{} {}",
-                        &cmd.data.name, &sub.name
-                    ))),
-                }
+/// Shows the number of open threads in the bot's status while `/presence` is on, updating it
+/// whenever `changed` is notified, but at most every [`PRESENCE_DEBOUNCE`].
+async fn update_presence(ctx: Context, pool: SqlitePool, changed: Arc<Notify>) {
+    let config = Config::new(pool.clone());
+    let mut shown = false;
+    loop {
+        changed.notified().await;
+        let res = async {
+            if config.get(ShowThreadCount).await? == Some(true) {
+                let count = Room::count(&pool).await?;
+                ctx.set_activity(Activity::watching(format!(
+                    "{} open thread{}",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                )))
+                .await;
+                shown = true;
+            } else if shown {
+                ctx.reset_presence().await;
+                shown = false;
             }
+            Ok::<_, Error>(())
+        }
+        .await;
 
-            "block" => {
-                if !perms.manage_roles() {
-                    return Err(Error::User(
-                        "You don't have `Manage Roles` permission.".into(),
-                    ));
-                }
-
-                let role = self.get_blockrole().await.and_then(|opt| {
-                    opt.ok_or_else(|| Error::User("There's no block role defined.".into()))
-                })?;
-
-                let codename = cmd.data.options.get(0).unwrap().resolved.as_ref().unwrap();
-                if let OptionValue::String(codename) = codename {
-                    let room = self.room_from_codename(codename).await.and_then(|opt| {
-                        opt.ok_or_else(|| {
-                            Error::User(format!("No thread with codename `{}` found.", codename))
-                        })
-                    })?;
+        if let Err(err) = res {
+            tracing::error!(source = ?err, "Error while updating presence.");
+        }
+        tokio::time::sleep(PRESENCE_DEBOUNCE).await;
+    }
+}
 
-                    let mut member = self.guild.member(ctx, room.user_id).await.map_err(|_| {
-                        Error::User("User is not a member or the server is unavailable.".into())
-                    })?;
+#[async_trait]
+impl EventHandler for Bot {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        // a guild chosen with `/rebind-guild` outlives restarts
+        match self.config.get(Guild).await {
+            Ok(Some(guild)) => self.guild.store(guild, Ordering::SeqCst),
+            Ok(None) => {}
+            Err(err) => tracing::error!(source = ?err, "Error while fetching guild override."),
+        }
 
-                    member.add_role(ctx, role).await.map_err(|_| {
-                        Error::User(
-                            "Missing permissions or configured block role is invalid.".into(),
-                        )
-                    })?;
+        if !ready.guilds.iter().any(|guild| guild.id == self.guild()) {
+            tracing::error!(
+                "Bot is not a member of guild {}. Check DISCORD_GUILD and the guild set with \
+                /rebind-guild, or invite the bot to it.",
+                self.guild()
+            );
+            self.shutdown.notify_one();
+            return;
+        }
 
-                    Ok(format!("Blocked `{}`.", &codename))
-                } else {
-                    panic!("got wrong option value")
-                }
+        if !self.registered.swap(true, Ordering::SeqCst) {
+            if let Err(err) = self.register_commands(&ctx).await {
+                tracing::error!(
+                    source = ?err,
+                    "Failed to register commands in guild {}. Make sure the bot was invited with \
+                    the `applications.commands` scope.",
+                    self.guild()
+                );
+                self.shutdown.notify_one();
+                return;
             }
+        }
 
-            "close" => {
-                if !perms.manage_channels() {
-                    return Err(Error::User(
-                        "You don't have `Manage Channels` permission.".into(),
-                    ));
-                }
+        if !self.reminding.swap(true, Ordering::SeqCst) {
+            tokio::spawn(deliver_reminders(self.pool.clone(), ctx.http.clone()));
+            tokio::spawn(deliver_scheduled(self.pool.clone(), ctx.http.clone()));
+            tokio::spawn(alert_unanswered(self.pool.clone(), ctx.http.clone()));
+        }
 
-                let codename = cmd.data.options.get(0).unwrap().resolved.as_ref().unwrap();
-                if let OptionValue::String(codename) = codename {
-                    let room = self.room_from_codename(codename).await.and_then(|opt| {
-                        opt.ok_or_else(|| {
-                            Error::User(format!("No thread with codename `{}` found.", codename))
-                        })
-                    })?;
+        if !self.maintaining.swap(true, Ordering::SeqCst) {
+            tokio::spawn(run_maintenance(self.pool.clone()));
+        }
 
-                    // notify user of thread closure 
+        // the status doesn't survive reconnecting
+        self.presence.notify_one();
+        if !self.presenting.swap(true, Ordering::SeqCst) {
+            tokio::spawn(update_presence(
+                ctx.clone(),
+                self.pool.clone(),
+                self.presence.clone(),
+            ));
+        }
 
-                    let usr = room.user_id.to_user(ctx).await.map_err(|_| {
-                        BotError::UserError(
-                            "User is not a member or the server is unavailable.".into(),
-                        )
-                    })?;
+        if let Err(err) = self.reconcile(&ctx).await {
+            tracing::error!(source = ?err, "Error while reconciling rooms.");
+        }
 
-                    usr.direct_message(ctx, |f| {
-                        f.embed(|e| {
-                            e.title("Thread closed");
-                            e.description(format!(
-                                "The thread with codename `{}` has been closed.",
-                                codename
-                            ));
-                            e
-                        })
-                    }).await.map_err(|_| {
-                        BotError::UserError(
-                            "DM Failed".into(),
-                        )
-                    })?;
+        if !self.inbox_checked.swap(true, Ordering::SeqCst) {
+            if let Err(err) = self.check_inbox(&ctx).await {
+                tracing::error!(source = ?err, "Error while checking inbox permissions.");
+            }
+        }
+    }
 
-                    let _ = room
-                        .channel_id
-                        .edit_thread(ctx, |edit| edit.archived(true))
-                        .await;
+    async fn thread_update(&self, _: Context, thread: GuildChannel) {
+        let archived = match &thread.thread_metadata {
+            Some(metadata) => metadata.archived,
+            None => return,
+        };
 
-                    self.delete_room(room.room_id).await?;
-                    Ok(format!(
-                        "Archived `{}` and removed attached user.",
-                        &codename
-                    ))
-                } else {
-                    panic!("got wrong option value")
+        let res = async {
+            if let Some(mut room) = Room::get_by_channel(&self.pool, thread.id).await? {
+                if room.archived != archived {
+                    room.set_archived(&self.pool, archived).await?;
                 }
             }
+            Ok::<_, Error>(())
+        }
+        .await;
 
-            _ => Err(Error::UnknownCommand(cmd.data.name.clone())),
+        if let Err(err) = res {
+            tracing::error!(source = ?err, "Error while handling thread update.");
         }
     }
 
-    async fn handle_message(&self, ctx: &Context, msg: &Message) -> Result<Option<String>> {
-        if msg.author.id == ctx.cache.current_user().id {
-            return Ok(None);
+    async fn guild_member_addition(&self, ctx: Context, mut member: Member) {
+        if member.guild_id != self.guild() {
+            return;
         }
 
-        if msg.is_private() {
-            match self.get_blockrole().await? {
-                Some(role) => {
-                    let blocked = msg
-                        .author
-                        .has_role(ctx, self.guild, role)
-                        .await
-                        .map_err(anyhow::Error::from)?;
-
-                    if blocked {
-                        return Ok(Some("You have been blocked by a server admin.".into()));
-                    }
-                }
+        let res = async {
+            if !Block::exists(&self.pool, member.user.id).await? {
+                return Ok(());
+            }
 
-                None => (),
+            if let Some(role) = self.config.get(Blockrole).await? {
+                member
+                    .add_role(&ctx, role)
+                    .await
+                    .map_err(anyhow::Error::from)?;
             }
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        if let Err(err) = res {
+            tracing::error!(source = ?err, user = %member.user.id, "Error while reapplying block role.");
+        }
+    }
 
-            if let Some(room) = self.room_from_user(msg.author.id.0).await? {
-                let content = MessageBuilder::new().push_safe(&msg.content).build();
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        _: Option<Member>,
+    ) {
+        if guild_id != self.guild() {
+            return;
+        }
+
+        let res = async {
+            for room in Room::for_user(&self.pool, user.id).await? {
                 room.channel_id
-                    .send_message(ctx, |createmsg| createmsg.content(content))
+                    .say(&ctx, MEMBER_LEFT_MESSAGE)
                     .await
                     .map_err(anyhow::Error::from)?;
+            }
+            Ok::<_, Error>(())
+        }
+        .await;
 
-                Ok(None)
-            } else {
-                let inbox = match self.get_inbox().await? {
-                    Some(inbox) => inbox,
-                    None => return Ok(None),
-                };
+        if let Err(err) = res {
+            tracing::warn!(source = ?err, user = %user.id, "Failed to note that a user left.");
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let show_footer = self.show_footer().await;
+        match interaction {
+            Interaction::ApplicationCommand(cmd) => {
+                if cmd.guild_id != Some(self.guild()) {
+                    tracing::warn!(guild = ?cmd.guild_id, command = %cmd.data.name, "Ignoring command from outside the guild.");
+                    return;
+                }
 
-                let codename = loop {
-                    let candidate = petname::petname(2, " ");
-                    if !self.check_codename_exists(&candidate).await? {
-                        break candidate;
+                // some commands (e.g. `/broadcast`) can outlive the 3 second response window,
+                // and whether the response is ephemeral can only be chosen now
+                let ephemeral = self.ephemeral.contains(&cmd.data.name);
+                cmd.create_interaction_response(&ctx, |r| {
+                    r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                        .interaction_response_data(|data| data.ephemeral(ephemeral))
+                })
+                .await
+                .expect("failed to defer interaction response");
+
+                let res = self.execute_command(&ctx, &cmd).await;
+                let failed = res.is_err();
+                let (color, desc, attachment, page, confirm) = match res {
+                    Ok(res) => (
+                        Color::DARK_GREEN,
+                        res.content,
+                        res.attachment,
+                        res.page,
+                        res.confirm,
+                    ),
+                    Err(err) => {
+                        if let Error::Internal(ref err) = err {
+                            tracing::error!(source = ?err, "Error while handling interaction.");
+                        }
+                        (Color::DARK_RED, err.to_string(), None, None, None)
                     }
                 };
+                // the full text of replies too long for an embed goes in a follow-up as well
+                let (desc, overflow) = embed::fit_description(desc);
+                let attachments = overflow
+                    .map(|full| (full.into_bytes(), embed::OVERFLOW_FILENAME.to_owned()))
+                    .into_iter()
+                    .chain(attachment);
 
-                let thread = {
-                    let inbox_msg = inbox
-                        .send_message(ctx, |f| {
-                            f.content("New thread created.")
-                                .embed(|e| {
-                                    e.title("New Modmail Received")
-                                    .field("Creator", format!("{}, ({})", &msg.author.mention(), &msg.author.tag()), true)
-                                    .field("Codename", &codename, true)
-                                })
-                            
-                        })
-                        .await
-                        .map_err(anyhow::Error::from)?;
+                let mut embed = CreateEmbed::default();
+                if show_footer {
+                    embed.footer(|foot| foot.text(FOOTER));
+                }
+                embed.description(desc).color(color);
 
-                    inbox
-                        .create_public_thread(ctx, inbox_msg.id, |thread| thread.name(&codename))
+                // errors are only meant for whoever used the command, so a public placeholder
+                // is swapped for an ephemeral follow-up
+                if failed && !ephemeral {
+                    cmd.delete_original_interaction_response(&ctx)
                         .await
-                        .map_err(anyhow::Error::from)?
-                };
-
-                let content = MessageBuilder::new().push_safe(&msg.content).build();
-                thread
-                    .send_message(ctx, |createmsg| {
-                        createmsg.embed(|f| {
-                            f.author(|a|{
-                                a.name(&msg.author.tag())
-                                .icon_url(&msg.author.face())
-                            })
-                            .description(&content)
-                        })
+                        .expect("failed to delete interaction response");
+                    cmd.create_followup_message(&ctx, |msg| {
+                        msg.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                            .add_embed(embed)
                     })
                     .await
-                    .map_err(anyhow::Error::from)?;
-
-                self.new_room(&codename, thread.id.0, msg.author.id.0)
-                    .await?;
-
-                Ok(Some(format!(
-                    "You've been assigned the codename `{}`.",
-                    &codename
-                )))
-            }
-        } else {
-            let room = match self.room_from_channel(msg.channel_id.0).await? {
-                Some(room) => room,
-                None => return Ok(None),
-            };
+                    .expect("failed to send interaction response");
+                    return;
+                }
 
-            let content = MessageBuilder::new().push_safe(&msg.content).build();
-            room.user_id
-                .create_dm_channel(ctx)
-                .await
-                .map_err(anyhow::Error::from)?
-                .send_message(ctx, |msg| msg.content(content))
+                cmd.edit_original_interaction_response(&ctx, |res| {
+                    if let Some((page, pages)) = &page {
+                        res.components(|c| page.buttons(*pages, c));
+                    } else if let Some(confirm) = &confirm {
+                        res.components(|c| confirm.button(c));
+                    }
+                    res.add_embed(embed)
+                })
                 .await
-                .map_err(anyhow::Error::from)?;
-
-            Ok(None)
-        }
-    }
-}
+                .expect("failed to send interaction response");
 
-#[async_trait]
-impl EventHandler for Bot {
-    async fn ready(&self, ctx: Context, _: Ready) {
-        self.guild
-            .set_application_commands(&ctx, |cmds| {
-                cmds.create_application_command(|cmd| {
-                    cmd.name("block")
-                        .description("Block a user from using the bot.")
-                        .kind(ApplicationCommandType::ChatInput)
-                        .create_option(|opt| {
-                            opt.name("codename")
-                                .description("The codename. Must be an exact match.")
-                                .kind(ApplicationCommandOptionType::String)
-                                .required(true)
-                        })
-                })
-                .create_application_command(|cmd| {
-                    cmd.name("blockrole")
-                        .description("Manage the role given to blocked users.")
-                        .kind(ApplicationCommandType::ChatInput)
-                        .create_option(|opt| {
-                            opt.name("set")
-                                .description("Set the role given to blocked users.")
-                                .kind(ApplicationCommandOptionType::SubCommand)
-                                .create_sub_option(|sub| {
-                                    sub.name("role")
-                                        .description("The role to be used.")
-                                        .kind(ApplicationCommandOptionType::Role)
-                                        .required(true)
-                                })
-                        })
-                        .create_option(|opt| {
-                            opt.name("unset")
-                                .description("Unset the block role.")
-                                .kind(ApplicationCommandOptionType::SubCommand)
-                        })
-                })
-                .create_application_command(|cmd| {
-                    cmd.name("inbox")
-                        .description("Manage the channel threads will be added to.")
-                        .kind(ApplicationCommandType::ChatInput)
-                        .create_option(|opt| {
-                            opt.name("set")
-                                .description("Set the channel threads will be added to.")
-                                .kind(ApplicationCommandOptionType::SubCommand)
-                                .create_sub_option(|sub| {
-                                    sub.name("channel")
-                                        .description("The channel to be used. Must allow threads.")
-                                        .kind(ApplicationCommandOptionType::Channel)
-                                        .channel_types(&[ChannelType::Text])
-                                        .required(true)
-                                })
-                        })
-                        .create_option(|opt| {
-                            opt.name("unset")
-                                .description("Unset the inbox channel.")
-                                .kind(ApplicationCommandOptionType::SubCommand)
-                        })
-                })
-                .create_application_command(|cmd| {
-                    cmd.name("close")
-                        .description("Close this thread and forget the attached user.")
-                        .kind(ApplicationCommandType::ChatInput)
-                        .create_option(|opt| {
-                            opt.name("codename")
-                                .description("The codename. Must be an exact match.")
-                                .kind(ApplicationCommandOptionType::String)
-                                .required(true)
+                // edited responses can't carry files, so attachments go in a follow-up
+                for (data, filename) in attachments {
+                    cmd.create_followup_message(&ctx, |msg| {
+                        if ephemeral {
+                            msg.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                        }
+                        msg.add_file(AttachmentType::Bytes {
+                            data: data.into(),
+                            filename,
                         })
-                })
-            })
-            .await
-            .expect("failed to register commands");
-    }
+                    })
+                    .await
+                    .expect("failed to send interaction response");
+                }
+            }
 
-    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Some(cmd) = interaction.application_command() {
-            assert_eq!(cmd.guild_id.unwrap(), self.guild);
-            let res = self.execute_command(&ctx, &cmd).await;
-            let (color, desc) = match res {
-                Ok(msg) => (Color::DARK_GREEN, msg),
-                Err(err) => {
-                    if let Error::Internal(ref err) = err {
-                        tracing::error!(source = ?err, "Error while handling interaction.");
+            Interaction::MessageComponent(component) => {
+                // confirmed actions can outlive the 3 second response window
+                component
+                    .create_interaction_response(&ctx, |r| {
+                        r.kind(InteractionResponseType::DeferredUpdateMessage)
+                    })
+                    .await
+                    .expect("failed to defer interaction response");
+
+                let res = match self.execute_component(&ctx, &component).await {
+                    Ok(res) => {
+                        component
+                            .edit_original_interaction_response(&ctx, |r| {
+                                match &res.page {
+                                    Some((page, pages)) => {
+                                        r.components(|c| page.buttons(*pages, c))
+                                    }
+                                    None => r.components(|c| c),
+                                };
+                                let mut embed = CreateEmbed::default();
+                                if show_footer {
+                                    embed.footer(|foot| foot.text(FOOTER));
+                                }
+                                // pages are short, but an update can't carry a file
+                                let (desc, _) = embed::fit_description(res.content);
+                                embed.description(desc).color(Color::DARK_GREEN);
+                                r.add_embed(embed)
+                            })
+                            .await
+                            .map(|_| ())
                     }
-                    (Color::DARK_RED, err.to_string())
+                    Err(err) => {
+                        if let Error::Internal(ref err) = err {
+                            tracing::error!(source = ?err, "Error while handling interaction.");
+                        }
+                        component
+                            .create_followup_message(&ctx, |msg| {
+                                msg.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                                    .embed(|emb| emb.description(err).color(Color::DARK_RED))
+                            })
+                            .await
+                            .map(|_| ())
+                    }
+                };
+                // the button may have deleted the thread it was in, taking the response along
+                if let Err(err) = res {
+                    tracing::warn!(source = ?err, "Failed to respond to button.");
                 }
-            };
+            }
 
-            cmd.create_interaction_response(&ctx, |res| {
-                res.interaction_response_data(|data| {
-                    data.embed(|emb| {
-                        emb.description(desc)
-                            .color(color)
-                            .footer(|foot| foot.text("With \u{2764} from the post office."))
-                    })
-                })
-            })
-            .await
-            .expect("failed to send interaction response");
+            _ => {}
         }
     }
 
@@ -545,12 +5364,14 @@ impl EventHandler for Bot {
         match res {
             Ok(content) => {
                 if let Some(content) = content {
+                    let show_footer = self.show_footer().await;
                     msg.channel_id
                         .send_message(&ctx, |send| {
                             send.reference_message(&msg).embed(|emb| {
-                                emb.color(Color::BLURPLE)
-                                    .description(content)
-                                    .footer(|foot| foot.text("With \u{2764} from the post office."))
+                                if show_footer {
+                                    emb.footer(|foot| foot.text(FOOTER));
+                                }
+                                emb.color(Color::BLURPLE).description(content)
                             })
                         })
                         .await
@@ -565,11 +5386,33 @@ impl EventHandler for Bot {
         }
     }
 
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _: Option<Message>,
+        _: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        if let Err(Error::Internal(err)) = self.handle_edit(&ctx, &event).await {
+            tracing::error!(source = ?err, "Error while handling message edit.");
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        if let Err(Error::Internal(err)) = self.handle_reaction(&ctx, &reaction).await {
+            tracing::error!(source = ?err, "Error while handling reaction.");
+        }
+    }
+
     async fn thread_delete(&self, _: Context, thread: PartialGuildChannel) {
-        let res = match self.room_from_channel(thread.id.0).await {
+        let res = match Room::get_by_channel(&self.pool, thread.id).await {
             Ok(opt) => {
                 if let Some(room) = opt {
-                    self.delete_room(room.room_id).await
+                    let res = room.delete(&self.pool).await;
+                    if res.is_ok() {
+                        self.presence.notify_one();
+                    }
+                    res
                 } else {
                     return;
                 }
@@ -582,31 +5425,3 @@ impl EventHandler for Bot {
         }
     }
 }
-
-#[derive(FromRow)]
-struct RawRoom {
-    room_id: i64,
-    codename: String,
-    channel_id: String,
-    user_id: String,
-}
-
-struct Room {
-    room_id: i64,
-    codename: String,
-    channel_id: ChannelId,
-    user_id: UserId,
-}
-
-impl TryFrom<RawRoom> for Room {
-    type Error = ParseIntError;
-
-    fn try_from(value: RawRoom) -> StdResult<Self, Self::Error> {
-        Ok(Self {
-            room_id: value.room_id,
-            codename: value.codename,
-            channel_id: value.channel_id.parse::<u64>()?.into(),
-            user_id: value.user_id.parse::<u64>()?.into(),
-        })
-    }
-}
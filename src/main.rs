@@ -2,14 +2,16 @@ use std::time::Duration;
 
 use anyhow::Context;
 use modmail::Bot;
-use serenity::{client::ClientBuilder, prelude::GatewayIntents};
+use serenity::{client::ClientBuilder, http::Http, prelude::GatewayIntents};
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
 
 const INTENTS: GatewayIntents = GatewayIntents::from_bits_truncate(
     GatewayIntents::DIRECT_MESSAGES.bits()
+        | GatewayIntents::DIRECT_MESSAGE_REACTIONS.bits()
+        | GatewayIntents::GUILD_MEMBERS.bits()
         | GatewayIntents::GUILD_MESSAGES.bits()
-        | GatewayIntents::GUILDS.bits()
-        | GatewayIntents::MESSAGE_CONTENT.bits(),
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS.bits()
+        | GatewayIntents::GUILDS.bits(),
 );
 
 #[tokio::main]
@@ -27,6 +29,27 @@ async fn main() -> anyhow::Result<()> {
         .parse()
         .context("DISCORD_GUILD is not a valid ID")?;
 
+    // the privileged message content intent needs approval for larger bots
+    let message_content: bool = match std::env::var("DISCORD_MESSAGE_CONTENT") {
+        Ok(value) => value
+            .parse()
+            .context("DISCORD_MESSAGE_CONTENT is not true or false")?,
+        Err(_) => true,
+    };
+    let intents = if message_content {
+        INTENTS | GatewayIntents::MESSAGE_CONTENT
+    } else {
+        INTENTS
+    };
+
+    let owner = {
+        let info = Http::new(&token)
+            .get_current_application_info()
+            .await
+            .context("failed to fetch application info")?;
+        info.team.map_or(info.owner.id, |team| team.owner_user_id)
+    };
+
     let pool = {
         let opts = SqliteConnectOptions::new()
             .create_if_missing(true)
@@ -45,20 +68,23 @@ async fn main() -> anyhow::Result<()> {
         .await
         .context("failed to migrate")?;
 
-    let bot = Bot::new(pool.clone(), guild);
-    let mut client = ClientBuilder::new(token, INTENTS)
+    let bot = Bot::new(pool.clone(), guild, owner).with_message_content(message_content);
+    let shutdown = bot.shutdown_signal();
+    let mut client = ClientBuilder::new(token, intents)
         .application_id(appid)
         .event_handler(bot)
         .await
         .context("failed to build client")?;
 
-    tokio::select! {
-        res = client.start() => res.context("failed to start client")?,
+    let res = tokio::select! {
+        res = client.start() => res.context("failed to start client"),
+        _ = shutdown.notified() => Err(anyhow::anyhow!("shutting down after a fatal error")),
         _ = tokio::signal::ctrl_c() => {
             tracing::warn!("caught interrupt signal (Ctrl-C), exiting...");
+            Ok(())
         },
     };
 
     pool.close().await;
-    Ok(())
+    res
 }
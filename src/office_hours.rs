@@ -0,0 +1,101 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// A daily window during which staff are available, e.g. `09:00-17:00 Europe/Berlin`.
+///
+/// Windows where `start` is after `end` wrap around midnight.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub tz: Tz,
+}
+
+impl Schedule {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.tz).time();
+        if self.start <= self.end {
+            self.start <= local && local < self.end
+        } else {
+            local >= self.start || local < self.end
+        }
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}",
+            self.start.format(TIME_FORMAT),
+            self.end.format(TIME_FORMAT),
+            self.tz.name()
+        )
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (range, tz) = s
+            .split_once(' ')
+            .ok_or_else(|| "expected `HH:MM-HH:MM Timezone`".to_owned())?;
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| "expected `HH:MM-HH:MM`".to_owned())?;
+
+        Ok(Self {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+            tz: tz
+                .parse()
+                .map_err(|_| format!("unknown timezone `{}`", tz))?,
+        })
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, TIME_FORMAT).map_err(|_| format!("invalid time `{}`", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveTime, TimeZone, Utc};
+
+    use super::Schedule;
+
+    #[test]
+    fn schedule_roundtrip() {
+        let hours: Schedule = "09:00-17:30 Europe/Berlin".parse().unwrap();
+        assert_eq!(hours.start, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        assert_eq!(hours.end, NaiveTime::from_hms_opt(17, 30, 0).unwrap());
+        assert_eq!(hours.to_string(), "09:00-17:30 Europe/Berlin");
+
+        assert!("09:00-17:00".parse::<Schedule>().is_err());
+        assert!("9am-5pm UTC".parse::<Schedule>().is_err());
+        assert!("09:00-17:00 Mars/Olympus".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn schedule_contains() {
+        // Berlin is UTC+2 in summer
+        let hours: Schedule = "09:00-17:00 Europe/Berlin".parse().unwrap();
+        assert!(hours.contains(Utc.with_ymd_and_hms(2022, 6, 1, 7, 0, 0).unwrap()));
+        assert!(hours.contains(Utc.with_ymd_and_hms(2022, 6, 1, 14, 59, 0).unwrap()));
+        assert!(!hours.contains(Utc.with_ymd_and_hms(2022, 6, 1, 15, 0, 0).unwrap()));
+        assert!(!hours.contains(Utc.with_ymd_and_hms(2022, 6, 1, 6, 59, 0).unwrap()));
+
+        let overnight: Schedule = "22:00-06:00 UTC".parse().unwrap();
+        assert!(overnight.contains(Utc.with_ymd_and_hms(2022, 6, 1, 23, 0, 0).unwrap()));
+        assert!(overnight.contains(Utc.with_ymd_and_hms(2022, 6, 1, 3, 0, 0).unwrap()));
+        assert!(!overnight.contains(Utc.with_ymd_and_hms(2022, 6, 1, 12, 0, 0).unwrap()));
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serenity::{builder::CreateComponents, model::interactions::message_component::ButtonStyle};
+
+use crate::custom_id;
+
+/// How many entries are shown per page.
+pub const PAGE_SIZE: usize = 15;
+
+/// How long page buttons keep working after the list was first requested.
+pub const PAGE_TIMEOUT_MINUTES: i64 = 15;
+
+const PREFIX: &str = "page";
+
+/// A page of a list, as encoded in the custom ID of the buttons leading to it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Page {
+    /// The command whose results are being paged through.
+    pub query: String,
+    pub index: usize,
+    /// When the list was first requested, for expiring old buttons.
+    pub created_at: DateTime<Utc>,
+}
+
+impl Page {
+    pub fn first(query: &str) -> Self {
+        Self {
+            query: query.to_owned(),
+            index: 0,
+            created_at: custom_id::now(),
+        }
+    }
+
+    pub fn parse(custom_id: &str) -> Option<Self> {
+        let (fields, created_at) = custom_id::decode(PREFIX, custom_id)?;
+        let (query, index) = match fields[..] {
+            [query, index] => (query, index),
+            _ => return None,
+        };
+
+        Some(Self {
+            query: query.to_owned(),
+            index: index.parse().ok()?,
+            created_at,
+        })
+    }
+
+    pub fn custom_id(&self) -> String {
+        let fields = [self.query.clone(), self.index.to_string()];
+        custom_id::encode(PREFIX, &fields, self.created_at)
+    }
+
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        custom_id::expired(self.created_at, now, PAGE_TIMEOUT_MINUTES)
+    }
+
+    /// Renders this page of `entries`, returning its content and how many pages there are.
+    /// Pages past the end, e.g. because entries were removed since, show the last page.
+    pub fn render(&mut self, entries: &[String]) -> (String, usize) {
+        let pages = entries.len().div_ceil(PAGE_SIZE).max(1);
+        self.index = self.index.min(pages - 1);
+
+        let start = self.index * PAGE_SIZE;
+        let end = (start + PAGE_SIZE).min(entries.len());
+        let mut content = entries[start..end].join("\n");
+        if pages > 1 {
+            content.push_str(&format!("\n\nPage {}/{}", self.index + 1, pages));
+        }
+        (content, pages)
+    }
+
+    /// Adds buttons leading to the previous and next page.
+    pub fn buttons<'a>(
+        &self,
+        pages: usize,
+        components: &'a mut CreateComponents,
+    ) -> &'a mut CreateComponents {
+        let to = |index| Self {
+            query: self.query.clone(),
+            index,
+            created_at: self.created_at,
+        };
+
+        components.create_action_row(|row| {
+            row.create_button(|button| {
+                button
+                    .custom_id(to(self.index.saturating_sub(1)).custom_id())
+                    .label("Prev")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(self.index == 0)
+            })
+            .create_button(|button| {
+                button
+                    .custom_id(to(self.index + 1).custom_id())
+                    .label("Next")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(self.index + 1 >= pages)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{Page, PAGE_SIZE};
+
+    #[test]
+    fn page_custom_id() {
+        let page = Page {
+            query: "list".into(),
+            index: 2,
+            created_at: Utc.timestamp_opt(1_650_000_000, 0).unwrap(),
+        };
+        assert_eq!(page.custom_id(), "page:list:2:1650000000");
+        assert_eq!(Page::parse(&page.custom_id()), Some(page));
+
+        assert_eq!(Page::parse("page:list:two:1650000000"), None);
+        assert_eq!(Page::parse("page:list:2:1650000000:extra"), None);
+        assert_eq!(Page::parse("something:else"), None);
+    }
+
+    #[test]
+    fn page_render() {
+        let entries: Vec<_> = (0..PAGE_SIZE + 1).map(|i| i.to_string()).collect();
+        let mut page = Page::first("list");
+        let (content, pages) = page.render(&entries);
+        assert_eq!(pages, 2);
+        assert!(content.starts_with("0\n1\n"));
+        assert!(content.ends_with("Page 1/2"));
+
+        page.index = 5;
+        let (content, _) = page.render(&entries);
+        assert_eq!(page.index, 1);
+        assert_eq!(content, format!("{}\n\nPage 2/2", PAGE_SIZE));
+
+        let (content, pages) = Page::first("list").render(&entries[..1]);
+        assert_eq!((content.as_str(), pages), ("0", 1));
+    }
+
+    #[test]
+    fn page_expiry() {
+        let page = Page::first("list");
+        assert!(!page.expired(Utc::now()));
+        assert!(page.expired(Utc::now() + Duration::hours(1)));
+    }
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use serenity::{
+    http::{routing::Route, Http, HttpError, StatusCode},
+    Error as SerenityError,
+};
+
+/// Logs what Discord said about a failed request, singling out rate limits.
+///
+/// serenity already retries rate-limited requests on its own, so a 429 reaching us means
+/// the bucket stayed exhausted; the time until it resets is looked up from serenity's
+/// ratelimiter so operators can tell which operation is being throttled.
+pub async fn log_failure(http: &Http, err: &SerenityError) {
+    let res = match err {
+        SerenityError::Http(err) => match err.as_ref() {
+            HttpError::UnsuccessfulRequest(res) => res,
+            _ => return,
+        },
+        _ => return,
+    };
+
+    let path = res.url.path();
+    if res.status_code == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = reset_after(http, path).await;
+        tracing::warn!(
+            route = path,
+            retry_after = ?retry_after,
+            message = %res.error.message,
+            "Rate limited by Discord."
+        );
+    } else {
+        tracing::warn!(
+            route = path,
+            status = %res.status_code,
+            code = res.error.code,
+            message = %res.error.message,
+            "Discord rejected request."
+        );
+    }
+}
+
+/// How long until a request that failed with `err` may be retried, if it was rate limited.
+/// Defaults to `fallback` when serenity doesn't know when the bucket resets.
+pub async fn retry_after(http: &Http, err: &SerenityError, fallback: Duration) -> Option<Duration> {
+    match err {
+        SerenityError::Http(err) => match err.as_ref() {
+            HttpError::UnsuccessfulRequest(res)
+                if res.status_code == StatusCode::TOO_MANY_REQUESTS =>
+            {
+                Some(reset_after(http, res.url.path()).await.unwrap_or(fallback))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Discord's error code for messages to users who closed their DMs or share no server with the
+/// bot.
+const CANNOT_MESSAGE_USER: isize = 50007;
+
+/// Why a DM didn't go through, as far as Discord's response tells.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DmFailure {
+    /// The user doesn't accept DMs from the bot, either because of their settings or because
+    /// they share no server with it, which only membership can tell apart.
+    Undeliverable,
+    RateLimited,
+    Other,
+}
+
+pub fn dm_failure(err: &SerenityError) -> DmFailure {
+    match err {
+        SerenityError::Http(err) => match err.as_ref() {
+            HttpError::UnsuccessfulRequest(res) => classify(res.status_code, res.error.code),
+            _ => DmFailure::Other,
+        },
+        _ => DmFailure::Other,
+    }
+}
+
+fn classify(status: StatusCode, code: isize) -> DmFailure {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        DmFailure::RateLimited
+    } else if code == CANNOT_MESSAGE_USER {
+        DmFailure::Undeliverable
+    } else {
+        DmFailure::Other
+    }
+}
+
+/// Discord's error code for looking up someone who isn't a member of the server.
+const UNKNOWN_MEMBER: isize = 10007;
+
+/// Whether `err` is Discord saying the user isn't a member of the server, e.g. because they
+/// left it.
+pub fn unknown_member(err: &SerenityError) -> bool {
+    match err {
+        SerenityError::Http(err) => matches!(
+            err.as_ref(),
+            HttpError::UnsuccessfulRequest(res) if res.error.code == UNKNOWN_MEMBER
+        ),
+        _ => false,
+    }
+}
+
+/// When the ratelimit bucket of the request to `path` resets, according to serenity.
+async fn reset_after(http: &Http, path: &str) -> Option<Duration> {
+    let route = route_from_path(path)?;
+    let routes = http.ratelimiter.routes();
+    let routes = routes.read().await;
+    let limit = routes.get(&route)?;
+    let reset_after = limit.lock().await.reset_after();
+    reset_after
+}
+
+/// Maps the path of a message-sending or thread-creating request back to serenity's
+/// ratelimit bucket.
+fn route_from_path(path: &str) -> Option<Route> {
+    let mut segments = path.trim_end_matches('/').rsplit('/');
+    match (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) {
+        (Some("messages"), Some(id), Some("channels"), _, _) => {
+            id.parse().ok().map(Route::ChannelsIdMessages)
+        }
+        (Some("threads"), Some(_), Some("messages"), Some(id), Some("channels")) => {
+            id.parse().ok().map(Route::ChannelsIdMessagesIdThreads)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::http::{routing::Route, StatusCode};
+
+    use super::{classify, route_from_path, DmFailure};
+
+    #[test]
+    fn classify_dm_failures() {
+        assert_eq!(
+            classify(StatusCode::FORBIDDEN, 50007),
+            DmFailure::Undeliverable
+        );
+        assert_eq!(
+            classify(StatusCode::TOO_MANY_REQUESTS, 0),
+            DmFailure::RateLimited
+        );
+        assert_eq!(classify(StatusCode::NOT_FOUND, 10003), DmFailure::Other);
+    }
+
+    #[test]
+    fn route_from_message_paths() {
+        assert_eq!(
+            route_from_path("/api/v9/channels/123/messages"),
+            Some(Route::ChannelsIdMessages(123))
+        );
+        assert_eq!(route_from_path("/api/v9/channels/123/messages/456"), None);
+        assert_eq!(route_from_path("/api/v9/users/@me/channels"), None);
+        assert_eq!(route_from_path("/api/v9/channels/abc/messages"), None);
+        assert_eq!(
+            route_from_path("/api/v9/channels/123/messages/456/threads"),
+            Some(Route::ChannelsIdMessagesIdThreads(123))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use serenity::model::id::MessageId;
+
+/// How long a user's reaction with the same emoji on the same message is not reported again.
+pub const DEBOUNCE_SECONDS: i64 = 60;
+
+/// Remembers which reactions were reported recently, so toggling one doesn't flood the thread.
+#[derive(Default)]
+pub struct Debouncer(Mutex<HashMap<(MessageId, String), DateTime<Utc>>>);
+
+impl Debouncer {
+    /// Whether a reaction with `emoji` on `message` should be reported at `now`, marking it as
+    /// reported if so.
+    pub fn allow(&self, message: MessageId, emoji: &str, now: DateTime<Utc>) -> bool {
+        let window = Duration::seconds(DEBOUNCE_SECONDS);
+        let mut reported = self.0.lock().unwrap();
+        reported.retain(|_, at| now - *at < window);
+
+        let key = (message, emoji.to_owned());
+        if reported.contains_key(&key) {
+            return false;
+        }
+        reported.insert(key, now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{Debouncer, DEBOUNCE_SECONDS};
+
+    #[test]
+    fn debounce_reactions() {
+        let debouncer = Debouncer::default();
+        let now = Utc::now();
+        assert!(debouncer.allow(1.into(), "👍", now));
+        assert!(!debouncer.allow(1.into(), "👍", now + Duration::seconds(5)));
+        assert!(debouncer.allow(1.into(), "👎", now));
+        assert!(debouncer.allow(2.into(), "👍", now));
+
+        let later = now + Duration::seconds(DEBOUNCE_SECONDS);
+        assert!(debouncer.allow(1.into(), "👍", later));
+    }
+}
@@ -0,0 +1,27 @@
+/// Substitutes every `{name}` placeholder in `template` with its value from `vars`.
+///
+/// Unknown placeholders are left untouched.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter().fold(template.to_owned(), |acc, (name, value)| {
+        acc.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+
+    #[test]
+    fn render_placeholders() {
+        let vars = [("codename", "peaceful bonefish"), ("reason", "resolved")];
+        assert_eq!(
+            render("Thread `{codename}` closed: {reason}", &vars),
+            "Thread `peaceful bonefish` closed: resolved"
+        );
+        assert_eq!(
+            render("{codename} {codename}", &vars),
+            "peaceful bonefish peaceful bonefish"
+        );
+        assert_eq!(render("{unknown}", &vars), "{unknown}");
+    }
+}
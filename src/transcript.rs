@@ -0,0 +1,105 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serenity::{
+    http::Http,
+    model::{channel::Message, id::ChannelId},
+};
+
+/// Discord's limit on how many messages can be fetched at once.
+const FETCH_LIMIT: u64 = 100;
+
+/// Marks the start of a transcript that left out older messages.
+pub const OMITTED_MARKER: &str = "[earlier messages omitted]";
+
+/// A message as it appears in a transcript.
+pub struct Line {
+    pub at: DateTime<Utc>,
+    pub author: String,
+    pub content: String,
+}
+
+/// Fetches up to the `limit` most recent messages in `channel`, oldest first, along with whether
+/// older messages were left out.
+pub async fn fetch(
+    http: &Http,
+    channel: ChannelId,
+    limit: usize,
+) -> serenity::Result<(Vec<Message>, bool)> {
+    let mut messages: Vec<Message> = Vec::new();
+    let mut truncated = false;
+    loop {
+        let before = messages.last().map(|msg| msg.id);
+        let page = channel
+            .messages(http, |req| {
+                if let Some(before) = before {
+                    req.before(before);
+                }
+                req.limit(FETCH_LIMIT)
+            })
+            .await?;
+
+        let exhausted = (page.len() as u64) < FETCH_LIMIT;
+        messages.extend(page);
+        if messages.len() > limit {
+            messages.truncate(limit);
+            truncated = true;
+            break;
+        }
+        if exhausted {
+            break;
+        }
+    }
+
+    messages.reverse();
+    Ok((messages, truncated))
+}
+
+/// Renders a plain text transcript, one message per line.
+pub fn render(lines: &[Line], truncated: bool) -> String {
+    let mut out = String::new();
+    if truncated {
+        out.push_str(OMITTED_MARKER);
+        out.push('\n');
+    }
+
+    for line in lines {
+        writeln!(
+            out,
+            "[{}] {}: {}",
+            line.at.format("%Y-%m-%d %H:%M UTC"),
+            line.author,
+            line.content
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{render, Line};
+
+    #[test]
+    fn render_transcript() {
+        let lines = [
+            Line {
+                at: Utc.with_ymd_and_hms(2022, 6, 1, 12, 0, 0).unwrap(),
+                author: "quiet owl".into(),
+                content: "hi".into(),
+            },
+            Line {
+                at: Utc.with_ymd_and_hms(2022, 6, 1, 12, 5, 0).unwrap(),
+                author: "staff#0001".into(),
+                content: "hello!".into(),
+            },
+        ];
+        assert_eq!(
+            render(&lines, false),
+            "[2022-06-01 12:00 UTC] quiet owl: hi\n[2022-06-01 12:05 UTC] staff#0001: hello!\n"
+        );
+        assert!(render(&lines, true).starts_with("[earlier messages omitted]\n[2022"));
+    }
+}